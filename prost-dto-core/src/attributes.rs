@@ -1,7 +1,8 @@
 use std::fmt::{Display, Formatter};
 
-use darling::util::SpannedValue;
+use darling::util::{Override, SpannedValue};
 use darling::{FromDeriveInput, FromField, FromVariant};
+use syn::spanned::Spanned;
 use syn::{Field, Variant};
 
 #[derive(Debug, Clone)]
@@ -22,14 +23,34 @@ where
     pub fn is_from(&self) -> bool {
         matches!(self, Direction::FromProst(_))
     }
+}
 
+impl<F, I> Direction<F, I>
+where
+    F: Fallible,
+{
+    // True if we are generating a `TryFrom` (TryFromProst) conversion.
+    pub fn is_fallible(&self) -> bool {
+        match self {
+            Direction::FromProst(info) => info.is_fallible(),
+            Direction::IntoProst(_) => false,
+        }
+    }
+}
+
+impl Direction<FromProstInfo, IntoProstInfo> {
     pub fn with_variant(
         &self,
         variant: &Variant,
     ) -> darling::Result<Direction<FromProstVariantInfo, IntoProstVariantInfo>> {
         Ok(match self {
-            Direction::FromProst(_) => {
-                Direction::FromProst(FromProstVariantInfo::from_variant(variant)?)
+            Direction::FromProst(info) => {
+                let mut variant_info = FromProstVariantInfo::from_variant(variant)?;
+                variant_info.fallible = info.fallible;
+                for field_info in variant_info.fields.fields.iter_mut() {
+                    field_info.fallible = info.fallible;
+                }
+                Direction::FromProst(variant_info)
             }
             Direction::IntoProst(_) => {
                 Direction::IntoProst(IntoProstVariantInfo::from_variant(variant)?)
@@ -42,12 +63,52 @@ where
         field: &Field,
     ) -> darling::Result<Direction<FromProstFieldInfo, IntoProstFieldInfo>> {
         Ok(match self {
-            Direction::FromProst(_) => Direction::FromProst(FromProstFieldInfo::from_field(field)?),
+            Direction::FromProst(info) => {
+                let mut field_info = FromProstFieldInfo::from_field(field)?;
+                field_info.fallible = info.fallible;
+                Direction::FromProst(field_info)
+            }
             Direction::IntoProst(_) => Direction::IntoProst(IntoProstFieldInfo::from_field(field)?),
         })
     }
 }
 
+impl Direction<FromProstFieldInfo, IntoProstFieldInfo> {
+    // True if the input is borrowed, see `ProstInfo::by_ref`.
+    pub fn is_by_ref(&self) -> bool {
+        match self {
+            Direction::FromProst(info) => info.by_ref,
+            Direction::IntoProst(info) => info.by_ref,
+        }
+    }
+
+    // True if the value is converted by a user-supplied `map`, `map_expr` or
+    // `try_map`.
+    pub fn has_user_mapper(&self) -> bool {
+        match self {
+            Direction::FromProst(info) => {
+                info.map.is_some() || info.map_expr.is_some() || info.try_map.is_some()
+            }
+            Direction::IntoProst(info) => info.map.is_some() || info.map_expr.is_some(),
+        }
+    }
+
+    // The `map_key` mapper of the keys of a map field.
+    pub fn map_key(&self) -> Option<&syn::Path> {
+        match self {
+            Direction::FromProst(info) => info.map_key.as_ref(),
+            Direction::IntoProst(info) => info.map_key.as_ref(),
+        }
+    }
+
+    pub fn set_by_ref(&mut self) {
+        match self {
+            Direction::FromProst(info) => info.by_ref = true,
+            Direction::IntoProst(info) => info.by_ref = true,
+        }
+    }
+}
+
 impl<A, B> Display for Direction<A, B> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -59,34 +120,92 @@ impl<A, B> Display for Direction<A, B> {
 
 // Attributes for struct/enum level #[prost(...)]
 #[derive(Debug, Clone, FromDeriveInput)]
-#[darling(attributes(prost), supports(struct_named, enum_newtype, enum_unit))]
+#[darling(
+    attributes(prost),
+    supports(
+        struct_named,
+        struct_newtype,
+        struct_tuple,
+        enum_named,
+        enum_newtype,
+        enum_unit
+    )
+)]
 pub(crate) struct ProstInfo {
     pub ident: syn::Ident,
+    pub vis: syn::Visibility,
+    pub generics: syn::Generics,
     pub target: syn::Path,
     pub oneof: Option<syn::Ident>,
     #[darling(default)]
     // If true, the generated match will include a default arm.
     pub non_exhaustive: SpannedValue<bool>,
+    // Convert a single-field struct directly to/from the target, which is the
+    // target of the inner type.
+    #[darling(default)]
+    pub transparent: SpannedValue<bool>,
+    // The sentinel (zero) variant of prost enumerations, `Unknown` by default.
+    #[darling(default)]
+    pub unknown: Option<syn::Ident>,
+    // The prost enumeration has no sentinel variant.
+    #[darling(default)]
+    pub no_unknown: SpannedValue<bool>,
+    // Only convert oneof enums to/from the oneof type, not the target
+    // message. For enums used as `#[prost(oneof)]` fields of other DTOs.
+    #[darling(default)]
+    pub embedded: SpannedValue<bool>,
+    // Where-predicates added to the generated impls, e.g. `T: Into<Foo>`.
+    #[darling(default)]
+    pub bound: Option<Vec<syn::WherePredicate>>,
+    // Also convert from references, e.g. `From<&Dto> for Target`, cloning
+    // only leaf values.
+    #[darling(default)]
+    pub by_ref: SpannedValue<bool>,
 }
 
 // Attributes for struct/enum level #[from_prost(...)]
 #[derive(Debug, Clone, FromDeriveInput)]
 #[darling(
     attributes(from_prost),
-    supports(struct_named, enum_newtype, enum_unit)
+    supports(
+        struct_named,
+        struct_newtype,
+        struct_tuple,
+        enum_named,
+        enum_newtype,
+        enum_unit
+    )
 )]
 pub(crate) struct FromProstInfo {
-    // Reserved for future use.
+    // Set by TryFromProst, we generate `TryFrom` instead of `From`.
+    #[darling(skip)]
+    pub fallible: bool,
+    // Convert i32 values of prost enumerations with the deprecated
+    // `from_i32` instead of `TryFrom<i32>`.
+    #[darling(default)]
+    pub use_from_i32: bool,
+    // Overrides #[prost(bound = "...")] for FromProst.
+    #[darling(default)]
+    pub bound: Option<Vec<syn::WherePredicate>>,
 }
 
 // Attributes for struct/enum level #[into_prost(...)]
 #[derive(Debug, Clone, FromDeriveInput)]
 #[darling(
     attributes(into_prost),
-    supports(struct_named, enum_newtype, enum_unit)
+    supports(
+        struct_named,
+        struct_newtype,
+        struct_tuple,
+        enum_named,
+        enum_newtype,
+        enum_unit
+    )
 )]
 pub(crate) struct IntoProstInfo {
-    // Reserved for future use.
+    // Overrides #[prost(bound = "...")] for IntoProst.
+    #[darling(default)]
+    pub bound: Option<Vec<syn::WherePredicate>>,
 }
 
 // Attributes for enum-variant level #[prost(...)]
@@ -96,30 +215,41 @@ pub(crate) struct IntoProstInfo {
 pub(crate) struct ProstVariantInfo {
     // automatically populated by darling
     pub ident: syn::Ident,
-    pub fields: darling::ast::Fields<ProstEnumFieldInfo>,
+    pub fields: darling::ast::Fields<ProstFieldInfo>,
     // our prost variant attributes
     #[darling(default)]
     pub name: Option<syn::Ident>,
+    // The payload message of struct-like and unit variants of oneof enums.
+    #[darling(default)]
+    pub target: Option<syn::Path>,
+    // Set if the variant is an arm of a oneof rather than a value of a prost
+    // enumeration.
+    #[darling(skip)]
+    pub oneof: bool,
     #[darling(default)]
     pub skip: bool,
+    // Holds the i32 values without a variant, e.g. `Unrecognized(i32)`.
+    #[darling(default)]
+    pub other: SpannedValue<bool>,
+    // Packs the variant into a `google.protobuf.Any` with this type URL, the
+    // message is set by `target`.
+    #[darling(default)]
+    pub type_url: Option<syn::LitStr>,
 }
 // Attributes for enum-variant level #[from_prost(...)]
 #[derive(Debug, Clone, FromVariant)]
 #[darling(attributes(from_prost))]
 pub(crate) struct FromProstVariantInfo {
-    // Reserved for future use.
+    pub fields: darling::ast::Fields<FromProstFieldInfo>,
+    // Inherited from the struct/enum level `FromProstInfo`
+    #[darling(skip)]
+    pub fallible: bool,
 }
 // Attributes for enum-variant level #[into_prost(...)]
 #[derive(Debug, Clone, FromVariant)]
 #[darling(attributes(into_prost))]
 pub(crate) struct IntoProstVariantInfo {
-    // Reserved for future use.
-}
-
-#[derive(Debug, Clone, FromField)]
-#[darling(attributes(prost))]
-pub(crate) struct ProstEnumFieldInfo {
-    // Reserved for future use.
+    pub fields: darling::ast::Fields<IntoProstFieldInfo>,
 }
 
 // Attributes for struct-field level #[prost(...)]
@@ -137,13 +267,103 @@ pub(crate) struct ProstFieldInfo {
     pub name: Option<syn::Ident>,
     #[darling(default)]
     pub required: bool,
+    // A oneof of the proto message, optionally naming the proto field. The
+    // field is required unless it's an `Option`.
+    #[darling(default)]
+    pub oneof: Option<Override<syn::Ident>>,
+    // Convert with `prost_dto::WellKnown`, for types that aren't detected
+    // automatically, or a `Vec<serde_json::Value>` from a `ListValue`. Needs
+    // the `prost-types` feature.
+    #[darling(default)]
+    pub well_known: SpannedValue<bool>,
+    // Convert byte strings (e.g. `Vec<u8>` or `[u8; 32]`) as a whole with
+    // `prost_dto::ProstBytes` instead of byte by byte, e.g. from a `Bytes`
+    // field. User mappers get the whole value too.
+    #[darling(default)]
+    pub bytes: SpannedValue<bool>,
+    // The field is a wrapper message, e.g. `google.protobuf.StringValue`. The
+    // message type is needed by IntoProst.
+    #[darling(default)]
+    pub wrapper: Option<Override<syn::Path>>,
+    // A `google.protobuf.Any` field, converted through an enum whose variants
+    // set `type_url`. The field is required unless it's an `Option`.
+    #[darling(default)]
+    pub any: SpannedValue<bool>,
+    // A module with `into_prost` and `from_prost` functions, used as the
+    // `map` of both directions.
+    #[darling(default)]
+    pub with: Option<syn::Path>,
+    // Like `with`, but `from_prost` returns a `Result` as a `try_map` does.
+    // Only supported by TryFromProst.
+    #[darling(default)]
+    pub try_with: Option<syn::Path>,
+    // The prost field boxes the message, e.g. `Option<Box<Node>>` for
+    // recursive messages.
+    #[darling(default)]
+    pub boxed: bool,
+    // The field type is a collection besides the built-in ones, it's
+    // converted element by element and collected with `FromIterator`.
+    #[darling(default)]
+    pub collection: bool,
+    // A map field converted into a `Vec` of `(key, value)` tuples or of
+    // entries with `key` and `value` fields.
+    #[darling(default)]
+    pub map_entries: SpannedValue<bool>,
+    // The prost enumeration of an i32 field. The sentinel of `Option<T>`
+    // fields is `None`, per the `ProstEnumeration` impl of `T`. Unknown values
+    // fail, so only TryFromProst converts from prost.
+    #[darling(default)]
+    pub enumeration: Option<syn::Path>,
+    // The field is a DTO converted from a reference (it sets `by_ref` too)
+    // instead of being cloned by `by_ref` conversions.
+    #[darling(default)]
+    pub by_ref: bool,
+    // ProstPatch applies nested mask paths (e.g. `address.city`) to the field,
+    // which must derive ProstPatch too.
+    #[darling(default)]
+    pub patch: SpannedValue<bool>,
+    // The position of the field, set for fields of tuple structs.
+    #[darling(skip)]
+    pub index: usize,
+    // Set while generating a conversion with a user mapper, which converts
+    // arrays and bytes values as a whole and other `Vec<u8>` byte by byte.
+    #[darling(skip)]
+    pub mapped: bool,
 }
 
 impl ProstFieldInfo {
-    // We only support structs with named fields (no tuples) so we can safely
-    // unwrap ident.
-    pub fn ident(&self) -> &syn::Ident {
-        self.ident.as_ref().unwrap()
+    pub fn span(&self) -> proc_macro2::Span {
+        match self.ident {
+            Some(ref ident) => ident.span(),
+            None => self.ty.span(),
+        }
+    }
+
+    // Our field, either named or positional (tuple structs).
+    pub fn member(&self) -> syn::Member {
+        match self.ident {
+            Some(ref ident) => syn::Member::Named(ident.clone()),
+            None => syn::Member::Unnamed(syn::Index {
+                index: self.index as u32,
+                span: self.ty.span(),
+            }),
+        }
+    }
+
+    // The prost field. Positional fields must be mapped to a named proto field
+    // via #[prost(name = "...")].
+    pub fn proto_member(&self) -> darling::Result<syn::Member> {
+        if let Some(Override::Explicit(ref oneof)) = self.oneof {
+            return Ok(syn::Member::Named(oneof.clone()));
+        }
+        match (&self.name, &self.ident) {
+            (Some(name), _) => Ok(syn::Member::Named(name.clone())),
+            (None, Some(ident)) => Ok(syn::Member::Named(ident.clone())),
+            (None, None) => Err(darling::Error::custom(
+                "Fields of tuple structs must set the proto field name with #[prost(name = \"...\")]",
+            )
+            .with_span(&self.ty)),
+        }
     }
 }
 
@@ -151,6 +371,13 @@ impl ProstFieldInfo {
 #[derive(Debug, Clone, FromField)]
 #[darling(attributes(from_prost))]
 pub(crate) struct FromProstFieldInfo {
+    // Inherited from the struct/enum level `FromProstInfo`
+    #[darling(skip)]
+    pub fallible: bool,
+    // Set when generating the conversion from a reference.
+    #[darling(skip)]
+    pub by_ref: bool,
+
     #[darling(default)]
     // Always set the value to None (if must be Option<T>) in FromProst
     // conversion, effectively making this a read-only field.
@@ -158,18 +385,44 @@ pub(crate) struct FromProstFieldInfo {
 
     #[darling(default)]
     pub map: Option<syn::Path>,
+    // Like `map` but a closure or any callable expression, e.g. `|v| v * 2`.
+    #[darling(default)]
+    pub map_expr: Option<syn::Expr>,
+    // Like `map` but the function returns a `Result`, only for TryFromProst.
+    #[darling(default)]
+    pub try_map: Option<syn::Path>,
     #[darling(default)]
     pub map_by_ref: SpannedValue<bool>,
+    // A repeated field converted into a set fails on duplicate values instead
+    // of dropping them, only for TryFromProst.
+    #[darling(default)]
+    pub deny_duplicates: SpannedValue<bool>,
+    // Converts the keys of a map field, the values are converted by `map`.
+    #[darling(default)]
+    pub map_key: Option<syn::Path>,
+    // Like `map_key` but the function returns a `Result`, only for
+    // TryFromProst.
+    #[darling(default)]
+    pub try_map_key: Option<syn::Path>,
 }
 
 // Attributes for struct-field level #[into_prost(...)]
 #[derive(Debug, Clone, FromField)]
 #[darling(attributes(into_prost))]
 pub(crate) struct IntoProstFieldInfo {
+    // Set when generating the conversion from a reference.
+    #[darling(skip)]
+    pub by_ref: bool,
     #[darling(default)]
     pub map: Option<syn::Path>,
+    // Like `map` but a closure or any callable expression.
+    #[darling(default)]
+    pub map_expr: Option<syn::Expr>,
     #[darling(default)]
     pub map_by_ref: SpannedValue<bool>,
+    // Converts the keys of a map field, the values are converted by `map`.
+    #[darling(default)]
+    pub map_key: Option<syn::Path>,
 }
 
 pub(crate) trait Skip {
@@ -187,3 +440,25 @@ impl Skip for ProstVariantInfo {
         self.skip
     }
 }
+
+pub(crate) trait Fallible {
+    fn is_fallible(&self) -> bool;
+}
+
+impl Fallible for FromProstInfo {
+    fn is_fallible(&self) -> bool {
+        self.fallible
+    }
+}
+
+impl Fallible for FromProstVariantInfo {
+    fn is_fallible(&self) -> bool {
+        self.fallible
+    }
+}
+
+impl Fallible for FromProstFieldInfo {
+    fn is_fallible(&self) -> bool {
+        self.fallible
+    }
+}
@@ -5,6 +5,7 @@ use quote::quote_spanned;
 use crate::attributes::{
     Direction, FromProstVariantInfo, IntoProstVariantInfo, ProstVariantInfo, Skip,
 };
+use crate::utils::to_snake_case;
 
 impl ProstVariantInfo {
     pub(crate) fn gen_tokens(
@@ -21,22 +22,187 @@ impl ProstVariantInfo {
             return Ok(TokenStream::new());
         }
 
+        let fallible = direction.is_fallible();
         let (s_variant, t_variant) = match direction {
             Direction::IntoProst(_) => (variant_name, target_variant_name),
             Direction::FromProst(_) => (target_variant_name, variant_name),
         };
 
+        if *self.other {
+            return Err(darling::Error::custom(
+                "`other` is only supported by enums mapped to prost enumerations",
+            )
+            .with_span(&self.other.span()));
+        }
+
+        if let Some(ref payload_type) = self.target
+            && (!self.oneof || self.fields.style == Style::Tuple)
+        {
+            return Err(darling::Error::custom(
+                "`target` is only supported by unit and struct-like variants of oneof enums",
+            )
+            .with_span(payload_type));
+        }
+
         match self.fields.style {
+            // A oneof arm carrying an empty message, e.g. `message Stop {}` or
+            // `google.protobuf.Empty` (which prost maps to `()`).
+            Style::Unit if self.oneof => {
+                let tokens = match (direction, self.target) {
+                    (Direction::IntoProst(_), Some(payload_type)) => quote_spanned! { span =>
+                        #source_type::#s_variant => #target_type::#t_variant(#payload_type {}),
+                    },
+                    (Direction::IntoProst(_), None) => quote_spanned! { span =>
+                        #source_type::#s_variant => #target_type::#t_variant(
+                            ::std::default::Default::default()
+                        ),
+                    },
+                    (Direction::FromProst(_), _) => quote_spanned! { span =>
+                        #source_type::#s_variant(_) => #target_type::#t_variant,
+                    },
+                };
+                Ok(tokens)
+            }
             Style::Unit => Ok(quote_spanned! { span =>
                 #source_type::#s_variant => #target_type::#t_variant,
             }),
+            Style::Tuple if fallible => {
+                // The oneof field name in the proto message.
+                let field_name = to_snake_case(&s_variant.to_string());
+                Ok(quote_spanned! { span =>
+                    #source_type::#s_variant(v) => #target_type::#t_variant(
+                        ::std::convert::TryInto::try_into(v)
+                            .map_err(|e| ::prost_dto::ConversionError::from(e).at_field(#field_name))?
+                    ),
+                })
+            }
             Style::Tuple => Ok(quote_spanned! { span =>
                 #source_type::#s_variant(v) => #target_type::#t_variant(v.into()),
             }),
-            Style::Struct => Err(darling::Error::unsupported_shape(
-                "Enums with struct-like fields are not supported",
-            )),
+            Style::Struct => {
+                // Struct-like variants are mapped to the message carried by the
+                // oneof arm, field by field.
+                let field_name = to_snake_case(&s_variant.to_string());
+                let is_into = direction.is_into();
+                if is_into && self.target.is_none() {
+                    return Err(darling::Error::custom(
+                        "Struct-like variants must set the payload message with \
+                         #[prost(target = \"...\")]",
+                    )
+                    .with_span(variant_name));
+                }
+                let mut acc = darling::Error::accumulator();
+                let mut bindings = Vec::new();
+                let mut field_tokens = Vec::new();
+                let field_directions: Vec<Direction<_, _>> = match direction {
+                    Direction::FromProst(variant_info) => variant_info
+                        .fields
+                        .into_iter()
+                        .map(Direction::FromProst)
+                        .collect(),
+                    Direction::IntoProst(variant_info) => variant_info
+                        .fields
+                        .into_iter()
+                        .map(Direction::IntoProst)
+                        .collect(),
+                };
+                for (field_info, field_direction) in
+                    self.fields.fields.into_iter().zip(field_directions)
+                {
+                    if !field_info.is_skipped() {
+                        bindings.push(field_info.member());
+                    }
+                    if let Some(field_tok) =
+                        acc.handle(field_info.gen_binding_tokens(field_direction))
+                    {
+                        field_tokens.push(field_tok);
+                    }
+                }
+
+                let tokens = if let Some(payload_type) = self.target.filter(|_| is_into) {
+                    quote_spanned! { span =>
+                        #source_type::#s_variant { #(#bindings,)* .. } => #target_type::#t_variant(
+                            #payload_type {
+                                #(#field_tokens)*
+                            }
+                        ),
+                    }
+                } else if fallible {
+                    quote_spanned! { span =>
+                        #source_type::#s_variant(value) => (|| {
+                            ::std::result::Result::<_, ::prost_dto::ConversionError>::Ok(
+                                #target_type::#t_variant {
+                                    #(#field_tokens)*
+                                }
+                            )
+                        })()
+                        .map_err(|e| e.at_field(#field_name))?,
+                    }
+                } else {
+                    quote_spanned! { span =>
+                        #source_type::#s_variant(value) => #target_type::#t_variant {
+                            #(#field_tokens)*
+                        },
+                    }
+                };
+                acc.finish_with(tokens)
+            }
+        }
+    }
+}
+
+impl ProstVariantInfo {
+    // The match arm of an enum packed into `google.protobuf.Any`. Each variant
+    // wraps a DTO of the `target` message, which is selected by `type_url`.
+    pub(crate) fn gen_any_tokens(
+        self,
+        direction: Direction<FromProstVariantInfo, IntoProstVariantInfo>,
+        enum_type: &TokenStream,
+    ) -> darling::Result<TokenStream> {
+        let span = self.ident.span();
+        let variant_name = &self.ident;
+
+        if self.is_skipped() {
+            return Ok(TokenStream::new());
+        }
+        if *self.other {
+            return Err(darling::Error::custom(
+                "`other` is only supported by enums mapped to prost enumerations",
+            )
+            .with_span(&self.other.span()));
         }
+        if self.fields.style != Style::Tuple || self.fields.len() != 1 {
+            return Err(darling::Error::custom(
+                "Variants of `Any` enums must have a single unnamed field",
+            )
+            .with_span(variant_name));
+        }
+        let (Some(type_url), Some(message)) = (&self.type_url, &self.target) else {
+            return Err(darling::Error::custom(
+                "Variants of `Any` enums must set both `type_url` and `target`",
+            )
+            .with_span(variant_name));
+        };
+
+        Ok(match direction {
+            Direction::IntoProst(_) => quote_spanned! { span =>
+                #enum_type::#variant_name(v) => ::prost_dto::pack_any(
+                    #type_url,
+                    &::std::convert::Into::<#message>::into(v),
+                ),
+            },
+            Direction::FromProst(_) => {
+                let field_name = to_snake_case(&variant_name.to_string());
+                quote_spanned! { span =>
+                    #type_url => #enum_type::#variant_name(
+                        ::std::convert::TryInto::try_into(
+                            ::prost_dto::unpack_any::<#message>(&value)?
+                        )
+                        .map_err(|e| ::prost_dto::ConversionError::from(e).at_field(#field_name))?
+                    ),
+                }
+            }
+        })
     }
 }
 
@@ -68,7 +234,7 @@ mod tests {
 
     #[test]
     fn gen_tokens_shape() -> darling::Result<()> {
-        // We don't support struct-style enums.
+        // Struct-like variants need to know the payload message.
         let variant: syn::Variant = parse_quote! { Something {x: i32, y: String} };
         let variant_info = ProstVariantInfo::from_variant(&variant)?;
 
@@ -85,13 +251,94 @@ mod tests {
 
         assert_eq!(
             maybe_tokens.unwrap_err().to_string(),
-            "Unsupported shape `Enums with struct-like fields are not \
-             supported`"
+            "Struct-like variants must set the payload message with \
+             #[prost(target = \"...\")]"
         );
 
         Ok(())
     }
 
+    #[test]
+    fn gen_tokens_struct() -> darling::Result<()> {
+        let variant: &syn::Variant = &parse_quote! {
+            #[prost(target = "proto::Move")]
+            Move {
+                x: i32,
+                #[prost(name = "why")]
+                y: Option<String>,
+                #[prost(skip)]
+                z: u64,
+            }
+        };
+        let mut variant_info = ProstVariantInfo::from_variant(variant)?;
+        variant_info.oneof = true;
+        let source_type = &parse_quote! { Foo };
+        let target_type = &parse_quote! { Bar };
+
+        let direction: Direction<FromProstVariantInfo, IntoProstVariantInfo> =
+            Direction::IntoProst(IntoProstVariantInfo::from_variant(variant)?);
+        gen_tokens_test_helper(
+            variant_info.clone(),
+            direction,
+            source_type,
+            target_type,
+            quote! {
+                Foo::Move { x, y, .. } => Bar::Move(
+                    proto::Move {
+                        x: x.into(),
+                        why: y.map(Into::into),
+                    }
+                ),
+            },
+        )?;
+
+        let direction: Direction<FromProstVariantInfo, IntoProstVariantInfo> =
+            Direction::FromProst(FromProstVariantInfo::from_variant(variant)?);
+        gen_tokens_test_helper(
+            variant_info.clone(),
+            direction,
+            source_type,
+            target_type,
+            quote! {
+                Foo::Move(value) => Bar::Move {
+                    x: value.x.into(),
+                    y: value.why.map(Into::into),
+                    z: ::std::default::Default::default(),
+                },
+            },
+        )?;
+
+        let mut from_variant_info = FromProstVariantInfo::from_variant(variant)?;
+        from_variant_info.fallible = true;
+        for field_info in from_variant_info.fields.fields.iter_mut() {
+            field_info.fallible = true;
+        }
+        gen_tokens_test_helper(
+            variant_info,
+            Direction::FromProst(from_variant_info),
+            source_type,
+            target_type,
+            quote! {
+                Foo::Move(value) => (|| {
+                    ::std::result::Result::<_, ::prost_dto::ConversionError>::Ok(
+                        Bar::Move {
+                            x: ::std::convert::TryInto::try_into(value.x)
+                                .map_err(::prost_dto::ConversionError::from)
+                                .map_err(|e| e.at_field("x"))?,
+                            y: value.why.map(|v| ::std::convert::TryInto::try_into(v)
+                                .map_err(::prost_dto::ConversionError::from))
+                                .transpose()
+                                .map_err(|e| e.at_field("why"))?,
+                            z: ::std::default::Default::default(),
+                        }
+                    )
+                })()
+                .map_err(|e| e.at_field("move"))?,
+            },
+        )?;
+        Ok(())
+    }
+
     #[test]
     fn gen_tokens_skipped() -> darling::Result<()> {
         // Normal
@@ -181,6 +428,67 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn gen_tokens_unit_oneof() -> darling::Result<()> {
+        let source_type = &parse_quote! { Foo };
+        let target_type = &parse_quote! { Bar };
+
+        // Default payload, e.g. `()` for `google.protobuf.Empty`.
+        let variant: &syn::Variant = &parse_quote! { Stop };
+        let mut variant_info = ProstVariantInfo::from_variant(variant)?;
+        variant_info.oneof = true;
+        gen_tokens_test_helper(
+            variant_info.clone(),
+            Direction::IntoProst(IntoProstVariantInfo::from_variant(variant)?),
+            source_type,
+            target_type,
+            quote! {
+                Foo::Stop => Bar::Stop(::std::default::Default::default()),
+            },
+        )?;
+        gen_tokens_test_helper(
+            variant_info,
+            Direction::FromProst(FromProstVariantInfo::from_variant(variant)?),
+            source_type,
+            target_type,
+            quote! {
+                Foo::Stop(_) => Bar::Stop,
+            },
+        )?;
+
+        // Named empty message.
+        let variant: &syn::Variant = &parse_quote! {
+            #[prost(target = "proto::Stop")]
+            Stop
+        };
+        let mut variant_info = ProstVariantInfo::from_variant(variant)?;
+        variant_info.oneof = true;
+        gen_tokens_test_helper(
+            variant_info.clone(),
+            Direction::IntoProst(IntoProstVariantInfo::from_variant(variant)?),
+            source_type,
+            target_type,
+            quote! {
+                Foo::Stop => Bar::Stop(proto::Stop {}),
+            },
+        )?;
+
+        // A prost enumeration has no payload.
+        variant_info.oneof = false;
+        let maybe_tokens = gen_tokens_test_helper(
+            variant_info,
+            Direction::IntoProst(IntoProstVariantInfo::from_variant(variant)?),
+            source_type,
+            target_type,
+            quote! {},
+        );
+        assert_eq!(
+            maybe_tokens.unwrap_err().to_string(),
+            "`target` is only supported by unit and struct-like variants of oneof enums"
+        );
+        Ok(())
+    }
+
     #[test]
     fn gen_tokens_non_unit() -> darling::Result<()> {
         {
@@ -255,4 +563,86 @@ mod tests {
         }
         Ok(())
     }
+
+    #[test]
+    fn gen_tokens_non_unit_fallible() -> darling::Result<()> {
+        let variant: &syn::Variant = &parse_quote! {
+            #[prost(name = "SomethingElse")]
+            Something(BigObject)
+        };
+        let variant_info = ProstVariantInfo::from_variant(variant)?;
+
+        let mut from_variant_info = FromProstVariantInfo::from_variant(variant)?;
+        from_variant_info.fallible = true;
+        let direction: Direction<FromProstVariantInfo, IntoProstVariantInfo> =
+            Direction::FromProst(from_variant_info);
+        let source_type = &parse_quote! { Foo };
+        let target_type = &parse_quote! { Bar };
+
+        gen_tokens_test_helper(
+            variant_info,
+            direction,
+            source_type,
+            target_type,
+            quote! {
+                Foo::SomethingElse(v) => Bar::Something(
+                    ::std::convert::TryInto::try_into(v)
+                        .map_err(|e| ::prost_dto::ConversionError::from(e).at_field("something_else"))?
+                ),
+            },
+        )?;
+        Ok(())
+    }
+
+    #[test]
+    fn gen_any_tokens() -> darling::Result<()> {
+        let variant: &syn::Variant = &parse_quote! {
+            #[prost(target = "proto::Click", type_url = "type.googleapis.com/acme.Click")]
+            Click(ClickDto)
+        };
+        let variant_info = ProstVariantInfo::from_variant(variant)?;
+        let enum_type = &parse_quote! { Payload };
+
+        let direction: Direction<FromProstVariantInfo, IntoProstVariantInfo> =
+            Direction::IntoProst(IntoProstVariantInfo::from_variant(variant)?);
+        let actual = variant_info.clone().gen_any_tokens(direction, enum_type)?;
+        let expected = quote! {
+            Payload::Click(v) => ::prost_dto::pack_any(
+                "type.googleapis.com/acme.Click",
+                &::std::convert::Into::<proto::Click>::into(v),
+            ),
+        };
+        assert_eq!(expected.to_string(), actual.to_string());
+
+        let mut from_variant_info = FromProstVariantInfo::from_variant(variant)?;
+        from_variant_info.fallible = true;
+        let direction: Direction<FromProstVariantInfo, IntoProstVariantInfo> =
+            Direction::FromProst(from_variant_info);
+        let actual = variant_info.gen_any_tokens(direction, enum_type)?;
+        let expected = quote! {
+            "type.googleapis.com/acme.Click" => Payload::Click(
+                ::std::convert::TryInto::try_into(
+                    ::prost_dto::unpack_any::<proto::Click>(&value)?
+                )
+                .map_err(|e| ::prost_dto::ConversionError::from(e).at_field("click"))?
+            ),
+        };
+        assert_eq!(expected.to_string(), actual.to_string());
+
+        // The payload message is needed to encode and decode the variant.
+        let variant: &syn::Variant = &parse_quote! {
+            #[prost(type_url = "type.googleapis.com/acme.Click")]
+            Click(ClickDto)
+        };
+        let direction: Direction<FromProstVariantInfo, IntoProstVariantInfo> =
+            Direction::IntoProst(IntoProstVariantInfo::from_variant(variant)?);
+        let err = ProstVariantInfo::from_variant(variant)?
+            .gen_any_tokens(direction, enum_type)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Variants of `Any` enums must set both `type_url` and `target`"
+        );
+        Ok(())
+    }
 }
@@ -0,0 +1,145 @@
+use darling::{Error, FromField};
+use proc_macro2::TokenStream;
+use quote::{ToTokens, format_ident, quote, quote_spanned};
+use syn::DeriveInput;
+
+use crate::attributes::{ProstFieldInfo, ProstInfo, Skip};
+
+// Generates the `ProstFields` impl and the `<Ident>FieldPaths` accessors of
+// the proto field paths.
+pub(crate) fn expand_prost_fields(
+    info: ProstInfo,
+    input: DeriveInput,
+) -> darling::Result<TokenStream> {
+    let syn::Data::Struct(struct_data) = input.data else {
+        return Err(Error::unsupported_shape(
+            "ProstFields can only be derived for structs",
+        ));
+    };
+
+    // error accumulator
+    let mut acc = darling::Error::accumulator();
+    let mut proto_fields = Vec::new();
+    let mut accessors = Vec::new();
+    for (index, field) in struct_data.fields.into_iter().enumerate() {
+        let Some(mut field_info) = acc.handle(ProstFieldInfo::from_field(&field)) else {
+            continue;
+        };
+        field_info.index = index;
+        if field_info.is_skipped() {
+            continue;
+        }
+        let Some(proto_name) = acc.handle(field_info.proto_member()) else {
+            continue;
+        };
+        let span = field_info.span();
+        let proto_name = proto_name.to_token_stream().to_string();
+        // Accessors are named after our fields, positional fields use the
+        // proto name.
+        let method = match field_info.ident {
+            Some(ref ident) => ident.clone(),
+            None => format_ident!("{}", proto_name, span = span),
+        };
+        let doc = format!("The path of the `{proto_name}` field.");
+        accessors.push(quote_spanned! { span =>
+            #[doc = #doc]
+            pub fn #method(&self) -> ::prost_dto::FieldPath {
+                self.0.join(#proto_name)
+            }
+        });
+        proto_fields.push(proto_name);
+    }
+
+    let ident = &info.ident;
+    let vis = &info.vis;
+    let paths_ident = format_ident!("{}FieldPaths", ident);
+    let paths_doc = format!("Field mask paths of [`{ident}`].");
+    let (impl_generics, ty_generics, where_clause) = info.generics.split_for_impl();
+    acc.finish_with(quote! {
+        #[doc = #paths_doc]
+        #[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+        #vis struct #paths_ident(::prost_dto::FieldPath);
+
+        #[automatically_derived]
+        impl ::std::convert::From<::prost_dto::FieldPath> for #paths_ident {
+            fn from(value: ::prost_dto::FieldPath) -> Self {
+                Self(value)
+            }
+        }
+
+        #[automatically_derived]
+        #[allow(clippy::all)]
+        impl #paths_ident {
+            #(#accessors)*
+        }
+
+        #[automatically_derived]
+        #[allow(clippy::all)]
+        impl #impl_generics ::prost_dto::ProstFields for #ident #ty_generics #where_clause {
+            const PROTO_FIELDS: &'static [&'static str] = &[#(#proto_fields),*];
+
+            type Paths = #paths_ident;
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use syn::parse_quote;
+
+    use super::*;
+
+    #[test]
+    fn expand_prost_fields_tokens() -> darling::Result<()> {
+        use darling::FromDeriveInput;
+
+        let input: DeriveInput = parse_quote! {
+            #[prost(target = "proto::Job")]
+            pub struct Job {
+                id: u64,
+                #[prost(name = "run_at")]
+                scheduled_at: u64,
+                #[prost(skip)]
+                cached: bool,
+            }
+        };
+        let info = ProstInfo::from_derive_input(&input)?;
+        let actual = expand_prost_fields(info, input)?;
+        let expected = quote! {
+            #[doc = "Field mask paths of [`Job`]."]
+            #[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+            pub struct JobFieldPaths(::prost_dto::FieldPath);
+
+            #[automatically_derived]
+            impl ::std::convert::From<::prost_dto::FieldPath> for JobFieldPaths {
+                fn from(value: ::prost_dto::FieldPath) -> Self {
+                    Self(value)
+                }
+            }
+
+            #[automatically_derived]
+            #[allow(clippy::all)]
+            impl JobFieldPaths {
+                #[doc = "The path of the `id` field."]
+                pub fn id(&self) -> ::prost_dto::FieldPath {
+                    self.0.join("id")
+                }
+                #[doc = "The path of the `run_at` field."]
+                pub fn scheduled_at(&self) -> ::prost_dto::FieldPath {
+                    self.0.join("run_at")
+                }
+            }
+
+            #[automatically_derived]
+            #[allow(clippy::all)]
+            impl ::prost_dto::ProstFields for Job {
+                const PROTO_FIELDS: &'static [&'static str] = &["id", "run_at"];
+
+                type Paths = JobFieldPaths;
+            }
+        };
+        assert_eq!(expected.to_string(), actual.to_string());
+        Ok(())
+    }
+}
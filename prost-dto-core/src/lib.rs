@@ -3,6 +3,8 @@ use proc_macro2::TokenStream;
 use syn::DeriveInput;
 mod attributes;
 mod enum_codegen;
+mod fields_codegen;
+mod patch_codegen;
 mod proto_conv;
 mod struct_codegen;
 mod utils;
@@ -29,12 +31,71 @@ pub fn derive_from_prost(input: DeriveInput) -> TokenStream {
     derive_prost(Direction::FromProst(from_info), input)
 }
 
+pub fn derive_try_from_prost(input: DeriveInput) -> TokenStream {
+    let mut from_info = match FromProstInfo::from_derive_input(&input) {
+        Ok(info) => info,
+        Err(e) => {
+            return e.write_errors();
+        }
+    };
+    from_info.fallible = true;
+    derive_prost(Direction::FromProst(from_info), input)
+}
+
+pub fn derive_prost_fields(input: DeriveInput) -> TokenStream {
+    let tokens = ProstInfo::from_derive_input(&input)
+        .and_then(|info| fields_codegen::expand_prost_fields(info, input));
+
+    match tokens {
+        Ok(tokens) => tokens,
+        Err(e) => e.write_errors(),
+    }
+}
+
+pub fn derive_prost_patch(input: DeriveInput) -> TokenStream {
+    let mut from_info = match FromProstInfo::from_derive_input(&input) {
+        Ok(info) => info,
+        Err(e) => {
+            return e.write_errors();
+        }
+    };
+    // Masked fields are converted like TryFromProst does.
+    from_info.fallible = true;
+    let tokens = ProstInfo::from_derive_input(&input).and_then(|info| {
+        patch_codegen::expand_prost_patch(Direction::FromProst(from_info), info, input)
+    });
+
+    match tokens {
+        Ok(tokens) => tokens,
+        Err(e) => e.write_errors(),
+    }
+}
+
+// Both IntoProst and FromProst, from a single parse of the `#[prost(...)]`
+// attributes.
+pub fn derive_prost_dto(input: DeriveInput) -> TokenStream {
+    let tokens = IntoProstInfo::from_derive_input(&input).and_then(|into_info| {
+        let from_info = FromProstInfo::from_derive_input(&input)?;
+        let info = ProstInfo::from_derive_input(&input)?;
+        let directions = vec![
+            Direction::IntoProst(into_info),
+            Direction::FromProst(from_info),
+        ];
+        proto_conv::expand_proto_conv(directions, info, input)
+    });
+
+    match tokens {
+        Ok(tokens) => tokens,
+        Err(e) => e.write_errors(),
+    }
+}
+
 fn derive_prost(
     direction: Direction<FromProstInfo, IntoProstInfo>,
     input: DeriveInput,
 ) -> TokenStream {
     let tokens = ProstInfo::from_derive_input(&input)
-        .and_then(|info| proto_conv::expand_proto_conv(direction, info, input));
+        .and_then(|info| proto_conv::expand_proto_conv(vec![direction], info, input));
 
     match tokens {
         Ok(tokens) => tokens,
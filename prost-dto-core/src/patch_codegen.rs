@@ -0,0 +1,238 @@
+use darling::{Error, FromField};
+use proc_macro2::TokenStream;
+use quote::{ToTokens, quote, quote_spanned};
+use syn::DeriveInput;
+
+use crate::attributes::{
+    Direction, FromProstFieldInfo, FromProstInfo, IntoProstFieldInfo, IntoProstInfo,
+    ProstFieldInfo, ProstInfo, Skip,
+};
+use crate::proto_conv::{ImplGenerics, split_generics};
+use crate::utils::{
+    extract_collection, extract_inner_type_from_container, map_segment, option_segment, vec_segment,
+};
+
+// Generates the `ProstPatch` impl. Masked fields are converted the same way
+// TryFromProst converts them.
+pub(crate) fn expand_prost_patch(
+    direction: Direction<FromProstInfo, IntoProstInfo>,
+    info: ProstInfo,
+    input: DeriveInput,
+) -> darling::Result<TokenStream> {
+    // `prost_dto::ProstPatch` reads a `google.protobuf.FieldMask`.
+    if !cfg!(feature = "prost-types") {
+        return Err(
+            Error::custom("ProstPatch needs the `prost-types` feature of prost-dto")
+                .with_span(&input.ident),
+        );
+    }
+    let syn::Data::Struct(struct_data) = input.data else {
+        return Err(Error::unsupported_shape(
+            "ProstPatch can only be derived for structs",
+        ));
+    };
+    if *info.transparent {
+        return Err(Error::custom("transparent is not supported by ProstPatch")
+            .with_span(&info.transparent.span()));
+    }
+    if *info.embedded {
+        return Err(Error::custom("embedded is only supported for oneof enums")
+            .with_span(&info.embedded.span()));
+    }
+
+    // error accumulator
+    let mut acc = darling::Error::accumulator();
+    let mut mask_paths = Vec::new();
+    let mut nested_arms = Vec::new();
+    let mut field_tokens = Vec::new();
+    for (index, field) in struct_data.fields.into_iter().enumerate() {
+        let Some(mut field_info) = acc.handle(ProstFieldInfo::from_field(&field)) else {
+            continue;
+        };
+        field_info.index = index;
+        let Some(field_direction) = acc.handle(direction.with_field(&field)) else {
+            continue;
+        };
+        if field_info.is_skipped() {
+            continue;
+        }
+        if let Some(tokens) = acc.handle(field_info.gen_patch_tokens(field_direction)) {
+            let (path, tokens, nested_arm) = tokens;
+            mask_paths.push(path);
+            field_tokens.push(tokens);
+            nested_arms.extend(nested_arm);
+        }
+    }
+
+    let ImplGenerics {
+        impl_generics,
+        self_type,
+        where_clause,
+    } = split_generics(&direction, &info);
+    let target = &info.target;
+    acc.finish_with(quote! {
+        #[automatically_derived]
+        #[allow(clippy::all)]
+        #[allow(unused_variables)]
+        impl #impl_generics ::prost_dto::ProstPatch for #self_type #where_clause {
+            type Proto = #target;
+
+            const MASK_PATHS: &'static [&'static str] = &[#(#mask_paths),*];
+
+            fn is_valid_mask_path(path: &str) -> bool {
+                match path.split_once('.') {
+                    ::std::option::Option::None => Self::MASK_PATHS.contains(&path),
+                    #(#nested_arms)*
+                    _ => false,
+                }
+            }
+
+            fn apply_proto_paths(
+                &mut self,
+                proto: Self::Proto,
+                paths: &[&str],
+            ) -> ::std::result::Result<(), ::prost_dto::ConversionError> {
+                #(#field_tokens)*
+                ::std::result::Result::Ok(())
+            }
+        }
+    })
+}
+
+impl ProstFieldInfo {
+    // Returns the mask path of the field, the statement applying it, and for
+    // `patch` fields the match arm validating nested paths.
+    fn gen_patch_tokens(
+        self,
+        direction: Direction<FromProstFieldInfo, IntoProstFieldInfo>,
+    ) -> darling::Result<(String, TokenStream, Option<TokenStream>)> {
+        let span = self.span();
+        let our_name = self.member();
+        let proto_name = self.proto_member()?;
+        let path = proto_name.to_token_stream().to_string();
+
+        if !*self.patch {
+            let value = self.gen_value(
+                direction,
+                quote_spanned! { span => proto.#proto_name },
+                Some(&path),
+            )?;
+            let tokens = quote_spanned! { span =>
+                if paths.contains(&#path) {
+                    self.#our_name = #value;
+                }
+            };
+            return Ok((path, tokens, None));
+        }
+
+        let is_container = [option_segment, vec_segment, map_segment]
+            .into_iter()
+            .any(|segment| extract_inner_type_from_container(&self.ty, segment).is_some())
+            || extract_collection(&self.ty, self.collection).is_some();
+        if is_container {
+            return Err(
+                Error::custom("`patch` fields must be a DTO deriving ProstPatch")
+                    .with_span(&self.patch.span()),
+            );
+        }
+        let ty = &self.ty;
+        let prefix = format!("{path}.");
+        // The whole message is applied with all of its paths. A missing nested
+        // message clears the masked fields.
+        let tokens = quote_spanned! { span =>
+            let nested_paths: ::std::vec::Vec<&str> = if paths.contains(&#path) {
+                <#ty as ::prost_dto::ProstPatch>::MASK_PATHS.to_vec()
+            } else {
+                paths
+                    .iter()
+                    .filter_map(|p| p.strip_prefix(#prefix))
+                    .collect()
+            };
+            if !nested_paths.is_empty() {
+                ::prost_dto::ProstPatch::apply_proto_paths(
+                    &mut self.#our_name,
+                    proto.#proto_name.unwrap_or_default(),
+                    &nested_paths,
+                )
+                .map_err(|e| e.at_field(#path))?;
+            }
+        };
+        let nested_arm = quote_spanned! { span =>
+            ::std::option::Option::Some((#path, rest)) => {
+                <#ty as ::prost_dto::ProstPatch>::is_valid_mask_path(rest)
+            }
+        };
+        Ok((path, tokens, Some(nested_arm)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use syn::parse_quote;
+
+    use super::*;
+
+    #[test]
+    fn gen_patch_tokens() -> darling::Result<()> {
+        let field: syn::Field = parse_quote! {
+            #[prost(name = "mail")]
+            email: String
+        };
+        let direction = Direction::FromProst(FromProstFieldInfo {
+            fallible: true,
+            ..FromProstFieldInfo::from_field(&field)?
+        });
+        let (path, tokens, nested_arm) =
+            ProstFieldInfo::from_field(&field)?.gen_patch_tokens(direction)?;
+        assert_eq!(path, "mail");
+        assert_eq!(
+            tokens.to_string(),
+            quote! {
+                if paths.contains(&"mail") {
+                    self.email = ::std::convert::TryInto::try_into(proto.mail)
+                        .map_err(::prost_dto::ConversionError::from)
+                        .map_err(|e| e.at_field("mail"))?;
+                }
+            }
+            .to_string()
+        );
+        assert!(nested_arm.is_none());
+
+        // Nested messages can't be patched through containers.
+        let field: syn::Field = parse_quote! {
+            #[prost(patch)]
+            address: Option<Address>
+        };
+        let direction = Direction::FromProst(FromProstFieldInfo::from_field(&field)?);
+        let err = ProstFieldInfo::from_field(&field)?
+            .gen_patch_tokens(direction)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "`patch` fields must be a DTO deriving ProstPatch"
+        );
+        Ok(())
+    }
+
+    #[cfg(not(feature = "prost-types"))]
+    #[test]
+    fn prost_patch_needs_prost_types() -> darling::Result<()> {
+        use darling::FromDeriveInput;
+
+        let input: DeriveInput = parse_quote! {
+            #[prost(target = "proto::User")]
+            struct User {
+                name: String,
+            }
+        };
+        let direction = Direction::FromProst(FromProstInfo::from_derive_input(&input)?);
+        let info = ProstInfo::from_derive_input(&input)?;
+        let err = expand_prost_patch(direction, info, input).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "ProstPatch needs the `prost-types` feature of prost-dto"
+        );
+        Ok(())
+    }
+}
@@ -1,68 +1,206 @@
+use darling::ast::Style;
 use darling::util::path_to_string;
 use darling::{Error, FromField, FromVariant};
-use proc_macro2::{Ident, TokenStream};
+use proc_macro2::{Ident, Span, TokenStream};
 use quote::{ToTokens, format_ident, quote, quote_spanned};
 use syn::spanned::Spanned;
 use syn::{DataEnum, DataStruct, DeriveInput};
 
 use crate::attributes::{
-    Direction, FromProstInfo, IntoProstInfo, ProstFieldInfo, ProstInfo, ProstVariantInfo,
+    Direction, FromProstInfo, IntoProstInfo, ProstFieldInfo, ProstInfo, ProstVariantInfo, Skip,
 };
 use crate::utils::{to_pascal_case, to_snake_case};
 
+// Generates the conversion impls of the `directions`, ProstDto derives both
+// IntoProst and FromProst at once.
 pub(crate) fn expand_proto_conv(
-    direction: Direction<FromProstInfo, IntoProstInfo>,
+    directions: Vec<Direction<FromProstInfo, IntoProstInfo>>,
     info: ProstInfo,
     input: DeriveInput,
 ) -> darling::Result<TokenStream> {
     // match if this is a struct or an enum
     match input.data {
-        syn::Data::Struct(data) => expand_struct(direction, info, data),
-        syn::Data::Enum(data) => expand_enum(direction, info, data),
-        _ => Err(Error::unsupported_shape(&format!(
-            "{} can only be derived for structs and enums",
-            direction
-        ))),
+        syn::Data::Struct(data) => expand_struct(directions, info, data),
+        syn::Data::Enum(data) => {
+            let mut errors = ImplErrors::default();
+            let tokens: TokenStream = directions
+                .into_iter()
+                .map(|direction| errors.handle(expand_enum(direction, info.clone(), data.clone())))
+                .collect();
+            errors.finish_with(tokens)
+        }
+        _ => {
+            let derives: Vec<_> = directions.iter().map(ToString::to_string).collect();
+            Err(Error::unsupported_shape(&format!(
+                "{} can only be derived for structs and enums",
+                derives.join(" and ")
+            )))
+        }
+    }
+}
+
+// Collects the errors of the impls of a derive. Errors of the attributes they
+// share are reported once, rather than by every impl.
+#[derive(Default)]
+struct ImplErrors(Vec<Error>);
+
+impl ImplErrors {
+    fn push(&mut self, errors: Error) {
+        for error in errors {
+            let reported = self.0.iter().any(|e| {
+                e.to_string() == error.to_string()
+                    && format!("{:?}", e.span()) == format!("{:?}", error.span())
+            });
+            if !reported {
+                self.0.push(error);
+            }
+        }
+    }
+
+    fn handle(&mut self, result: Result<TokenStream, Error>) -> TokenStream {
+        result.unwrap_or_else(|errors| {
+            self.push(errors);
+            TokenStream::new()
+        })
+    }
+
+    fn finish_with(self, tokens: TokenStream) -> Result<TokenStream, Error> {
+        if self.0.is_empty() {
+            Ok(tokens)
+        } else {
+            Err(Error::multiple(self.0))
+        }
     }
 }
 
 fn expand_struct(
-    direction: Direction<FromProstInfo, IntoProstInfo>,
+    directions: Vec<Direction<FromProstInfo, IntoProstInfo>>,
     info: ProstInfo,
     struct_data: DataStruct,
+) -> Result<TokenStream, Error> {
+    if *info.embedded {
+        return Err(Error::custom("embedded is only supported for oneof enums")
+            .with_span(&info.embedded.span()));
+    }
+    // The `#[prost(...)]` attributes of the fields are parsed once, for every
+    // impl.
+    let mut errors = ImplErrors::default();
+    let mut fields = Vec::with_capacity(struct_data.fields.len());
+    for (index, field) in struct_data.fields.iter().enumerate() {
+        match ProstFieldInfo::from_field(field) {
+            Ok(mut field_info) => {
+                field_info.index = index;
+                fields.push((field, field_info));
+            }
+            Err(e) => errors.push(e),
+        }
+    }
+    let mut tokens = TokenStream::new();
+    for direction in &directions {
+        tokens.extend(errors.handle(expand_struct_impl(direction, &info, &fields, false)));
+        if *info.by_ref {
+            tokens.extend(errors.handle(expand_struct_impl(direction, &info, &fields, true)));
+        }
+    }
+    errors.finish_with(tokens)
+}
+
+// Generates the conversion impl of a struct, from a reference if `by_ref`.
+fn expand_struct_impl(
+    direction: &Direction<FromProstInfo, IntoProstInfo>,
+    info: &ProstInfo,
+    fields: &[(&syn::Field, ProstFieldInfo)],
+    by_ref: bool,
 ) -> Result<TokenStream, Error> {
     // error accumulator
     let mut acc = darling::Error::accumulator();
-    let mut field_tokens = Vec::with_capacity(struct_data.fields.len());
-    for field in struct_data.fields {
-        let Some(field_info) = acc.handle(ProstFieldInfo::from_field(&field)) else {
-            continue;
-        };
+    let mut field_tokens = Vec::with_capacity(fields.len());
+    // The value of a transparent struct, in IntoProst it's the whole body.
+    let mut transparent_tok = None;
+    for (field, field_info) in fields {
+        let field_info = field_info.clone();
         // direction-wise #[direction_proto(..)] attributes
-        let Some(direction) = acc.handle(direction.with_field(&field)) else {
+        let Some(mut direction) = acc.handle(direction.with_field(field)) else {
             continue;
         };
+        if by_ref {
+            direction.set_by_ref();
+        }
+        if *info.transparent && !field_info.is_skipped() {
+            if transparent_tok.is_some() {
+                acc.push(
+                    Error::custom("transparent structs must have exactly one non-skipped field")
+                        .with_span(&info.transparent.span()),
+                );
+                continue;
+            }
+            transparent_tok = acc.handle(field_info.gen_transparent(direction));
+            continue;
+        }
         let field_tok = acc.handle(field_info.gen_tokens(direction));
         if let Some(field_tok) = field_tok {
             field_tokens.push(field_tok);
         }
     }
 
-    let (from_type, for_type) = match direction {
-        Direction::FromProst(_) => (info.target.to_token_stream(), info.ident.to_token_stream()),
-        Direction::IntoProst(_) => (info.ident.to_token_stream(), info.target.to_token_stream()),
+    let body = match transparent_tok {
+        Some(transparent_tok) if direction.is_into() => transparent_tok,
+        Some(transparent_tok) => quote! {
+            Self {
+                #transparent_tok
+                #(#field_tokens)*
+            }
+        },
+        None if *info.transparent => {
+            return Err(Error::custom(
+                "transparent structs must have exactly one non-skipped field",
+            )
+            .with_span(&info.transparent.span()));
+        }
+        None => quote! {
+            Self {
+                #(#field_tokens)*
+            }
+        },
     };
 
-    let tokens = quote! {
-    #[automatically_derived]
-    #[allow(clippy::all)]
-    impl ::std::convert::From<#from_type> for #for_type {
-                fn from(value: #from_type) -> Self {
-                    Self {
-                        #(#field_tokens)*
-                    }
+    let ImplGenerics {
+        impl_generics,
+        self_type,
+        where_clause,
+    } = split_generics(direction, info);
+    let (mut from_type, for_type) = match direction {
+        Direction::FromProst(_) => (info.target.to_token_stream(), self_type),
+        Direction::IntoProst(_) => (self_type, info.target.to_token_stream()),
+    };
+    let impl_generics = if by_ref {
+        from_type = quote! { &'prost_dto_ref #from_type };
+        ref_impl_generics(direction, info)
+    } else {
+        impl_generics
+    };
 
-                }
+    let tokens = if direction.is_fallible() {
+        quote! {
+        #[automatically_derived]
+        #[allow(clippy::all)]
+        impl #impl_generics ::std::convert::TryFrom<#from_type> for #for_type #where_clause {
+                    type Error = ::prost_dto::ConversionError;
+
+                    fn try_from(value: #from_type) -> ::std::result::Result<Self, Self::Error> {
+                        ::std::result::Result::Ok(#body)
+                    }
+            }
+        }
+    } else {
+        quote! {
+        #[automatically_derived]
+        #[allow(clippy::all)]
+        impl #impl_generics ::std::convert::From<#from_type> for #for_type #where_clause {
+                    fn from(value: #from_type) -> Self {
+                        #body
+                    }
+            }
         }
     };
     acc.finish_with(tokens)
@@ -73,10 +211,30 @@ fn expand_enum(
     info: ProstInfo,
     enum_data: DataEnum,
 ) -> Result<TokenStream, Error> {
-    // We cheat by looking at the first variant to determine whether this is a
-    // unit-only enum or not. Ideally, we should fail if there is a mix of unit
-    // and unnamed.
-    if let syn::Fields::Unit = enum_data.variants.first().unwrap().fields {
+    if *info.transparent {
+        return Err(Error::custom("transparent is only supported for structs")
+            .with_span(&info.transparent.span()));
+    }
+    if *info.by_ref {
+        return Err(
+            Error::custom("by_ref is only supported for structs").with_span(&info.by_ref.span())
+        );
+    }
+    // Enums whose variants set `type_url` are packed into `google.protobuf.Any`.
+    let any = enum_data.variants.iter().any(|variant| {
+        ProstVariantInfo::from_variant(variant).is_ok_and(|info| info.type_url.is_some())
+    });
+    if any {
+        return expand_any_enum(direction, info, enum_data);
+    }
+    // A unit-only enum maps to a prost enumeration unless `oneof` is set. Any
+    // other enum maps to a oneof, where unit variants carry an empty message.
+    // The `#[prost(other)]` variant catching unrecognized values doesn't count.
+    let unit_only = enum_data.variants.iter().all(|variant| {
+        matches!(variant.fields, syn::Fields::Unit)
+            || ProstVariantInfo::from_variant(variant).is_ok_and(|info| *info.other)
+    });
+    if unit_only && info.oneof.is_none() {
         expand_unit_only_enum(direction, info, enum_data)
     } else {
         expand_non_unit_enum(direction, info, enum_data)
@@ -131,6 +289,7 @@ fn expand_non_unit_enum(
     // `bar` (or `foo` by default)
     let oneof_ident = info
         .oneof
+        .clone()
         .unwrap_or_else(|| target_tail_type_snakecase.clone());
     // `Bar`
     let oneof_ident_pascal = format_ident!("{}", to_pascal_case(&oneof_ident.to_string()));
@@ -150,9 +309,10 @@ fn expand_non_unit_enum(
 
     for variant in enum_data.variants {
         // general #[proto(..)] attributes
-        let Some(variant_info) = acc.handle(ProstVariantInfo::from_variant(&variant)) else {
+        let Some(mut variant_info) = acc.handle(ProstVariantInfo::from_variant(&variant)) else {
             continue;
         };
+        variant_info.oneof = true;
 
         // direction-wise #[direction_proto(..)] attributes
         let Some(direction) = acc.handle(direction.with_variant(&variant)) else {
@@ -166,53 +326,199 @@ fn expand_non_unit_enum(
         }
     }
 
+    let target_type_str = path_to_string(&info.target);
     if *info.non_exhaustive {
-        let span = info.non_exhaustive.span();
-        let target_type_str = path_to_string(&info.target);
-        variant_tokens.push(quote_spanned! { span =>
-            _e => {
-                // Missing variants.
-                panic!("Attempting to convert variant of `{:?}` with no match in the target type `{}`",
-                       _e, #target_type_str);
-            }
-        });
+        variant_tokens.push(non_exhaustive_arm(
+            &direction,
+            info.non_exhaustive.span(),
+            &target_type_str,
+        ));
     }
 
-    let (from_type, for_type) = match direction {
-        Direction::FromProst(_) => (info.target.to_token_stream(), info.ident.to_token_stream()),
-        Direction::IntoProst(_) => (info.ident.to_token_stream(), info.target.to_token_stream()),
+    let ImplGenerics {
+        impl_generics,
+        self_type,
+        where_clause,
+    } = split_generics(&direction, &info);
+
+    // The conversion between our enum and the oneof enum. This is what oneof
+    // fields of struct DTOs use.
+    let oneof_tokens = match direction {
+        Direction::IntoProst(_) => quote! {
+            #[automatically_derived]
+            #[allow(clippy::all)]
+            #[allow(unreachable_patterns)]
+            impl #impl_generics ::std::convert::From<#self_type> for #target_type #where_clause {
+                fn from(value: #self_type) -> Self {
+                    match value {
+                        #(#variant_tokens)*
+                    }
+                }
+            }
+        },
+        Direction::FromProst(_) if direction.is_fallible() => quote! {
+            #[automatically_derived]
+            #[allow(clippy::all)]
+            #[allow(unreachable_patterns)]
+            impl #impl_generics ::std::convert::TryFrom<#source_type> for #self_type #where_clause {
+                type Error = ::prost_dto::ConversionError;
+
+                fn try_from(value: #source_type) -> ::std::result::Result<Self, Self::Error> {
+                    ::std::result::Result::Ok(match value {
+                        #(#variant_tokens)*
+                    })
+                }
+            }
+        },
+        Direction::FromProst(_) => quote! {
+            #[automatically_derived]
+            #[allow(clippy::all)]
+            #[allow(unreachable_patterns)]
+            impl #impl_generics ::std::convert::From<#source_type> for #self_type #where_clause {
+                fn from(value: #source_type) -> Self {
+                    match value {
+                        #(#variant_tokens)*
+                    }
+                }
+            }
+        },
     };
 
-    let body = match direction {
-        Direction::IntoProst(_) => {
-            quote! {
-                let o = match value {
-                    #(#variant_tokens)*
-                };
-                Self {
-                    #oneof_ident: ::std::option::Option::Some(o),
+    // `embedded` enums are only used as oneof fields of other DTOs, the target
+    // message has more fields than the oneof.
+    if *info.embedded {
+        return acc.finish_with(oneof_tokens);
+    }
+
+    let target = &info.target;
+    let oneof_ident_str = oneof_ident.to_string();
+    let message_tokens = match direction {
+        Direction::IntoProst(_) => quote! {
+            #[automatically_derived]
+            #[allow(clippy::all)]
+            impl #impl_generics ::std::convert::From<#self_type> for #target #where_clause {
+                fn from(value: #self_type) -> Self {
+                    Self {
+                        #oneof_ident: ::std::option::Option::Some(::std::convert::Into::into(value)),
+                    }
+                }
+            }
+        },
+        Direction::FromProst(_) if direction.is_fallible() => quote! {
+            #[automatically_derived]
+            #[allow(clippy::all)]
+            impl #impl_generics ::std::convert::TryFrom<#target> for #self_type #where_clause {
+                type Error = ::prost_dto::ConversionError;
+
+                fn try_from(value: #target) -> ::std::result::Result<Self, Self::Error> {
+                    let o = value.#oneof_ident.ok_or_else(|| {
+                        ::prost_dto::ConversionError::missing_oneof(#oneof_ident_str)
+                    })?;
+                    ::std::convert::TryFrom::try_from(o)
+                }
+            }
+        },
+        Direction::FromProst(_) => quote! {
+            #[automatically_derived]
+            #[allow(clippy::all)]
+            impl #impl_generics ::std::convert::From<#target> for #self_type #where_clause {
+                fn from(value: #target) -> Self {
+                    ::std::convert::From::from(value.#oneof_ident.unwrap())
                 }
             }
+        },
+    };
+
+    acc.finish_with(quote! {
+        #oneof_tokens
+        #message_tokens
+    })
+}
+
+fn expand_any_enum(
+    direction: Direction<FromProstInfo, IntoProstInfo>,
+    info: ProstInfo,
+    enum_data: DataEnum,
+) -> Result<TokenStream, Error> {
+    if *info.embedded {
+        return Err(Error::custom("embedded is only supported for oneof enums")
+            .with_span(&info.embedded.span()));
+    }
+    if let Some(ref oneof) = info.oneof {
+        return Err(Error::custom("`oneof` is not supported by `Any` enums").with_span(oneof));
+    }
+    // Unknown type URLs and undecodable payloads can't be handled by `From`.
+    if direction.is_from() && !direction.is_fallible() {
+        return Err(
+            Error::custom("Enums packed into `Any` are only supported by TryFromProst")
+                .with_span(&info.ident),
+        );
+    }
+    // error accumulator
+    let mut acc = darling::Error::accumulator();
+    let ImplGenerics {
+        impl_generics,
+        self_type,
+        where_clause,
+    } = split_generics(&direction, &info);
+    let enum_ident = info.ident.to_token_stream();
+
+    let mut variant_tokens: Vec<_> = Vec::with_capacity(enum_data.variants.len());
+    for variant in enum_data.variants {
+        let Some(variant_info) = acc.handle(ProstVariantInfo::from_variant(&variant)) else {
+            continue;
+        };
+        let Some(direction) = acc.handle(direction.with_variant(&variant)) else {
+            continue;
+        };
+        if let Some(variant_tok) = acc.handle(variant_info.gen_any_tokens(direction, &enum_ident)) {
+            variant_tokens.push(variant_tok);
         }
-        Direction::FromProst(_) => {
+    }
+
+    let target = &info.target;
+    let tokens = match direction {
+        Direction::IntoProst(_) => {
+            // Skipped variants have no message to be packed into.
+            if *info.non_exhaustive {
+                variant_tokens.push(non_exhaustive_arm(
+                    &direction,
+                    info.non_exhaustive.span(),
+                    &path_to_string(target),
+                ));
+            }
             quote! {
-                match value.#oneof_ident.unwrap() {
-                    #(#variant_tokens)*
+                #[automatically_derived]
+                #[allow(clippy::all)]
+                #[allow(unreachable_patterns)]
+                impl #impl_generics ::std::convert::From<#self_type> for #target #where_clause {
+                    fn from(value: #self_type) -> Self {
+                        match value {
+                            #(#variant_tokens)*
+                        }
+                    }
                 }
             }
         }
-    };
-    let tokens = quote! {
-        #[automatically_derived]
-        #[allow(clippy::all)]
-        #[allow(unreachable_patterns)]
-        impl ::std::convert::From<#from_type> for #for_type {
-            fn from(value: #from_type) -> Self {
-                #body
+        Direction::FromProst(_) => quote! {
+            #[automatically_derived]
+            #[allow(clippy::all)]
+            impl #impl_generics ::std::convert::TryFrom<#target> for #self_type #where_clause {
+                type Error = ::prost_dto::ConversionError;
+
+                fn try_from(value: #target) -> ::std::result::Result<Self, Self::Error> {
+                    ::std::result::Result::Ok(match value.type_url.as_str() {
+                        #(#variant_tokens)*
+                        type_url => {
+                            return ::std::result::Result::Err(
+                                ::prost_dto::ConversionError::unknown_type_url(type_url)
+                            );
+                        }
+                    })
+                }
             }
-        }
+        },
     };
-
     acc.finish_with(tokens)
 }
 
@@ -221,23 +527,72 @@ fn expand_unit_only_enum(
     info: ProstInfo,
     enum_data: DataEnum,
 ) -> Result<TokenStream, Error> {
+    if *info.embedded {
+        return Err(Error::custom("embedded is only supported for oneof enums")
+            .with_span(&info.embedded.span()));
+    }
     // error accumulator
     let mut acc = darling::Error::accumulator();
     let mut variant_tokens: Vec<_> = Vec::with_capacity(enum_data.variants.len());
 
+    // Used in match patterns, the generic arguments are inferred.
     let (from_type, for_type) = match direction {
         Direction::FromProst(_) => (info.target.to_token_stream(), info.ident.to_token_stream()),
         Direction::IntoProst(_) => (info.ident.to_token_stream(), info.target.to_token_stream()),
     };
+    let ImplGenerics {
+        impl_generics,
+        self_type,
+        where_clause,
+    } = split_generics(&direction, &info);
+
+    // The sentinel (zero) variant of the prost enumeration, `Unknown` unless
+    // configured otherwise.
+    if *info.no_unknown && info.unknown.is_some() {
+        acc.push(
+            Error::custom("`unknown` and `no_unknown` are mutually exclusive")
+                .with_span(&info.no_unknown.span()),
+        );
+    }
+    let sentinel = match info.unknown {
+        _ if *info.no_unknown => None,
+        Some(ref unknown) => Some(unknown.clone()),
+        None => Some(format_ident!("Unknown")),
+    };
+    // Whether one of our variants maps to the sentinel.
+    let mut sentinel_mapped = false;
+    // The `#[prost(other)]` variant holding unrecognized i32 values.
+    let mut other: Option<Ident> = None;
 
     for variant in enum_data.variants {
         let Some(variant_info) = acc.handle(ProstVariantInfo::from_variant(&variant)) else {
             continue;
         };
+        if *variant_info.other {
+            let span = variant_info.other.span();
+            if other.is_some() {
+                acc.push(Error::custom("only one variant can be marked `other`").with_span(&span));
+            } else if variant_info.fields.style != Style::Tuple || variant_info.fields.len() != 1 {
+                acc.push(
+                    Error::custom(
+                        "`other` variants must have a single i32 field, e.g. `Unrecognized(i32)`",
+                    )
+                    .with_span(&span),
+                );
+            } else {
+                other = Some(variant_info.ident);
+            }
+            continue;
+        }
         // direction-wise #[direction_proto(..)] attributes
         let Some(direction) = acc.handle(direction.with_variant(&variant)) else {
             continue;
         };
+        if !variant_info.is_skipped()
+            && sentinel.as_ref() == Some(variant_info.name.as_ref().unwrap_or(&variant_info.ident))
+        {
+            sentinel_mapped = true;
+        }
         let variant_tok = acc.handle(variant_info.gen_tokens(direction, &from_type, &for_type));
         if let Some(variant_tok) = variant_tok {
             variant_tokens.push(variant_tok);
@@ -245,25 +600,64 @@ fn expand_unit_only_enum(
     }
 
     let target_type_str = path_to_string(&info.target);
-    if *info.non_exhaustive {
-        let span = info.non_exhaustive.span();
-        variant_tokens.push(quote_spanned! { span =>
-            _e => {
-                // Missing variants.
-                panic!("Attempting to convert variant of `{:?}` with no match in the target type `{}`",
-                       _e, #target_type_str);
-            }
+    // Values without a variant are held by the `other` variant.
+    if let (Some(other), Direction::FromProst(_)) = (&other, &direction) {
+        variant_tokens.push(quote! {
+            _ => #for_type::#other(value as i32),
         });
+    } else if *info.non_exhaustive {
+        variant_tokens.push(non_exhaustive_arm(
+            &direction,
+            info.non_exhaustive.span(),
+            &target_type_str,
+        ));
     }
 
-    let tokens = match direction {
-        Direction::IntoProst(_) => {
+    let tokens = match (&direction, &other) {
+        (Direction::IntoProst(_), Some(other)) => {
+            // The raw value of the `other` variant can only be written to the
+            // i32 repr, it may not be a variant of the target.
             quote! {
                 #[automatically_derived]
                 #[allow(clippy::all)]
                 #[allow(unreachable_patterns)]
-                impl ::std::convert::From<#from_type> for #for_type {
-                    fn from(value: #from_type) -> Self {
+                impl #impl_generics ::std::convert::TryFrom<#self_type> for #for_type #where_clause {
+                    type Error = ::prost_dto::ConversionError;
+
+                    fn try_from(value: #self_type) -> ::std::result::Result<Self, Self::Error> {
+                        ::std::result::Result::Ok(match value {
+                            #from_type::#other(v) => {
+                                <#for_type as ::std::convert::TryFrom<i32>>::try_from(v).map_err(|_| {
+                                    ::prost_dto::ConversionError::unknown_enum_value(#target_type_str, v)
+                                })?
+                            }
+                            #(#variant_tokens)*
+                        })
+                    }
+                }
+
+                // Provides a conversion from our enum to i32's repr of the target proto
+                #[automatically_derived]
+                #[allow(clippy::all)]
+                #[allow(unreachable_patterns)]
+                impl #impl_generics ::std::convert::From<#self_type> for i32 #where_clause {
+                    fn from(value: #self_type) -> Self {
+                        let proto: #for_type = match value {
+                            #from_type::#other(v) => return v,
+                            #(#variant_tokens)*
+                        };
+                        proto as i32
+                    }
+                }
+            }
+        }
+        (Direction::IntoProst(_), None) => {
+            quote! {
+                #[automatically_derived]
+                #[allow(clippy::all)]
+                #[allow(unreachable_patterns)]
+                impl #impl_generics ::std::convert::From<#self_type> for #for_type #where_clause {
+                    fn from(value: #self_type) -> Self {
                         match value {
                             #(#variant_tokens)*
                         }
@@ -274,33 +668,265 @@ fn expand_unit_only_enum(
                 #[automatically_derived]
                 #[allow(clippy::all)]
                 #[allow(unreachable_patterns)]
-                impl ::std::convert::From<#from_type> for i32 {
-                    fn from(value: #from_type) -> Self {
+                impl #impl_generics ::std::convert::From<#self_type> for i32 #where_clause {
+                    fn from(value: #self_type) -> Self {
                        let proto: #for_type = ::std::convert::Into::into(value);
                        proto as i32
                     }
                 }
             }
         }
-        Direction::FromProst(_) => {
+        (Direction::FromProst(from_info), _) => {
+            // Prost enumerations implement `TryFrom<i32>`, older prost versions
+            // only provide the (now deprecated) `from_i32`.
+            let from_i32 = if from_info.use_from_i32 {
+                quote! { #from_type::from_i32(value) }
+            } else {
+                quote! { <#from_type as ::std::convert::TryFrom<i32>>::try_from(value).ok() }
+            };
+            // Unless one of our variants maps to it, the sentinel is rejected.
+            let sentinel_arm = match sentinel {
+                _ if other.is_some() => TokenStream::new(),
+                Some(ref sentinel) if !sentinel_mapped && direction.is_fallible() => quote! {
+                    #from_type::#sentinel => {
+                        return ::std::result::Result::Err(
+                            ::prost_dto::ConversionError::unknown_enum_value(
+                                #target_type_str,
+                                #from_type::#sentinel as i32,
+                            )
+                        );
+                    },
+                },
+                Some(ref sentinel) if !sentinel_mapped => {
+                    let sentinel_str = format!("{target_type_str}::{sentinel}");
+                    quote! {
+                        #from_type::#sentinel => {
+                            panic!("We should never see {}", #sentinel_str);
+                        },
+                    }
+                }
+                _ => TokenStream::new(),
+            };
+            // `Option<Self>` fields treat the sentinel as an unset value.
+            let sentinel_check = sentinel.as_ref().map(|sentinel| {
+                quote! {
+                    if value == #from_type::#sentinel as i32 {
+                        return ::std::result::Result::Ok(::std::option::Option::None);
+                    }
+                }
+            });
+            let unknown_value = match other {
+                Some(ref other) => quote! {
+                    match #from_i32 {
+                        ::std::option::Option::Some(enum_value) => enum_value,
+                        ::std::option::Option::None => {
+                            return ::std::result::Result::Ok(Self::#other(value));
+                        }
+                    }
+                },
+                None => quote! {
+                    #from_i32.ok_or_else(|| {
+                        ::prost_dto::ConversionError::unknown_enum_value(#target_type_str, value)
+                    })?
+                },
+            };
+            let (typed_tokens, convert) = if direction.is_fallible() {
+                let typed_tokens = quote! {
+                    #[automatically_derived]
+                    #[allow(clippy::all)]
+                    #[allow(unreachable_patterns)]
+                    impl #impl_generics ::std::convert::TryFrom<#from_type> for #self_type #where_clause {
+                        type Error = ::prost_dto::ConversionError;
+
+                        fn try_from(value: #from_type) -> ::std::result::Result<Self, Self::Error> {
+                            ::std::result::Result::Ok(match value {
+                                #sentinel_arm
+                                #(#variant_tokens)*
+                            })
+                        }
+                    }
+                };
+                (
+                    typed_tokens,
+                    quote! { ::std::convert::TryFrom::try_from(enum_value) },
+                )
+            } else {
+                let typed_tokens = quote! {
+                    #[automatically_derived]
+                    #[allow(clippy::all)]
+                    #[allow(unreachable_patterns)]
+                    impl #impl_generics ::std::convert::From<#from_type> for #self_type #where_clause {
+                        fn from(value: #from_type) -> Self {
+                            match value {
+                                #sentinel_arm
+                                #(#variant_tokens)*
+                            }
+                        }
+                    }
+                };
+                // The typed conversion panics on the sentinel, which we can
+                // report from the i32 one instead.
+                let sentinel_error = match sentinel {
+                    Some(ref sentinel) if !sentinel_mapped && other.is_none() => quote! {
+                        if ::std::matches!(enum_value, #from_type::#sentinel) {
+                            return ::std::result::Result::Err(
+                                ::prost_dto::ConversionError::unknown_enum_value(
+                                    #target_type_str,
+                                    value,
+                                )
+                            );
+                        }
+                    },
+                    _ => TokenStream::new(),
+                };
+                (
+                    typed_tokens,
+                    quote! {
+                        #sentinel_error
+                        ::std::result::Result::Ok(::std::convert::From::from(enum_value))
+                    },
+                )
+            };
             quote! {
+                #typed_tokens
 
-            #[automatically_derived]
-            #[allow(clippy::all)]
-            #[allow(unreachable_patterns)]
-            impl ::std::convert::From<i32> for #for_type {
-                fn from(value: i32) -> Self {
-                    let enum_value = #from_type::from_i32(value).unwrap();
-                    match enum_value {
-                        #from_type::Unknown => {
-                            panic!("We should never see {}", concat!(#target_type_str, "::Unknown"));
-                        },
-                        #(#variant_tokens)*
+                // Provides a conversion from the i32's repr of the target proto
+                #[automatically_derived]
+                #[allow(clippy::all)]
+                #[allow(deprecated)]
+                impl #impl_generics ::std::convert::TryFrom<i32> for #self_type #where_clause {
+                    type Error = ::prost_dto::ConversionError;
+
+                    fn try_from(value: i32) -> ::std::result::Result<Self, Self::Error> {
+                        let enum_value = #unknown_value;
+                        #convert
+                    }
+                }
+
+                #[automatically_derived]
+                #[allow(clippy::all)]
+                impl #impl_generics ::prost_dto::ProstEnumeration for #self_type #where_clause {
+                    fn try_from_i32_or_none(
+                        value: i32,
+                    ) -> ::std::result::Result<::std::option::Option<Self>, ::prost_dto::ConversionError> {
+                        #sentinel_check
+                        <Self as ::std::convert::TryFrom<i32>>::try_from(value)
+                            .map(::std::option::Option::Some)
                     }
                 }
-            }
             }
         }
     };
     acc.finish_with(tokens)
 }
+
+// The impl generics of `by_ref` conversions, with the lifetime of the borrowed
+// input.
+fn ref_impl_generics(
+    direction: &Direction<FromProstInfo, IntoProstInfo>,
+    info: &ProstInfo,
+) -> TokenStream {
+    let mut info = info.clone();
+    info.generics
+        .params
+        .insert(0, syn::parse_quote! { 'prost_dto_ref });
+    split_generics(direction, &info).impl_generics
+}
+
+// The generics of the generated impl blocks and the (generic) type of our
+// DTO.
+pub(crate) struct ImplGenerics {
+    pub impl_generics: TokenStream,
+    pub self_type: TokenStream,
+    pub where_clause: TokenStream,
+}
+
+pub(crate) fn split_generics(
+    direction: &Direction<FromProstInfo, IntoProstInfo>,
+    info: &ProstInfo,
+) -> ImplGenerics {
+    // We can't infer bounds on the type parameters since we don't know the
+    // types of the target fields. Users need to supply them via
+    // `#[prost(bound = "...")]` or the direction-wise variant which takes
+    // precedence.
+    let direction_bound = match direction {
+        Direction::FromProst(from_info) => from_info.bound.as_ref(),
+        Direction::IntoProst(into_info) => into_info.bound.as_ref(),
+    };
+    let mut generics = info.generics.clone();
+    if let Some(bound) = direction_bound.or(info.bound.as_ref()) {
+        generics
+            .make_where_clause()
+            .predicates
+            .extend(bound.iter().cloned());
+    }
+    let ident = &info.ident;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    ImplGenerics {
+        impl_generics: impl_generics.to_token_stream(),
+        self_type: quote! { #ident #ty_generics },
+        where_clause: where_clause.to_token_stream(),
+    }
+}
+
+// The catch-all match arm for `non_exhaustive` enums. Fallible conversions
+// return an error instead of panicking.
+fn non_exhaustive_arm(
+    direction: &Direction<FromProstInfo, IntoProstInfo>,
+    span: Span,
+    target_type_str: &str,
+) -> TokenStream {
+    if direction.is_fallible() {
+        quote_spanned! { span =>
+            _ => {
+                return ::std::result::Result::Err(
+                    ::prost_dto::ConversionError::unsupported_variant(#target_type_str)
+                );
+            }
+        }
+    } else {
+        quote_spanned! { span =>
+            _e => {
+                // Missing variants.
+                panic!("Attempting to convert variant of `{:?}` with no match in the target type `{}`",
+                       _e, #target_type_str);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use darling::FromDeriveInput;
+    use pretty_assertions::assert_eq;
+    use syn::parse_quote;
+
+    use super::*;
+
+    #[test]
+    fn expand_both_directions_reports_errors_once() {
+        let input: DeriveInput = parse_quote! {
+            #[prost(target = "proto::Blob")]
+            struct Blob {
+                #[prost(unknown_option)]
+                id: String,
+                #[prost(well_known, bytes)]
+                data: Vec<u8>,
+            }
+        };
+        let directions = vec![
+            Direction::IntoProst(IntoProstInfo::from_derive_input(&input).unwrap()),
+            Direction::FromProst(FromProstInfo::from_derive_input(&input).unwrap()),
+        ];
+        let info = ProstInfo::from_derive_input(&input).unwrap();
+        let err = expand_proto_conv(directions, info, input).unwrap_err();
+        let messages: Vec<_> = err.into_iter().map(|e| e.to_string()).collect();
+        assert_eq!(
+            messages,
+            [
+                "Unknown field: `unknown_option`",
+                "`well_known` and `bytes` are mutually exclusive",
+            ]
+        );
+    }
+}
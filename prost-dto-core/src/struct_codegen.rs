@@ -1,11 +1,13 @@
-use darling::util::SpannedValue;
+use darling::util::{Override, SpannedValue};
 use proc_macro2::TokenStream;
-use quote::{quote, quote_spanned};
+use quote::{ToTokens, quote, quote_spanned};
 use syn::spanned::Spanned;
 
 use crate::attributes::{Direction, FromProstFieldInfo, IntoProstFieldInfo, ProstFieldInfo, Skip};
 use crate::utils::{
-    extract_inner_type_from_container, hashmap_segment, map_segment, option_segment, vec_segment,
+    CollectionKind, SmartPointer, extract_collection, extract_inner_type_from_container,
+    extract_map_value_type, extract_smart_pointer, hashmap_segment, is_byte_string, is_bytes_type,
+    is_growable_bytes, is_json_list, is_well_known_type, map_segment, option_segment, vec_segment,
 };
 
 impl ProstFieldInfo {
@@ -13,21 +15,57 @@ impl ProstFieldInfo {
         self,
         direction: Direction<FromProstFieldInfo, IntoProstFieldInfo>,
     ) -> darling::Result<TokenStream> {
-        let span = self.ident.span();
+        self.gen_member_tokens(direction, false)
+    }
+
+    // Like `gen_tokens` but for fields of struct-like enum variants. In
+    // IntoProst, the field is read from the binding of the same name created
+    // by destructuring the variant instead of `value`.
+    pub(crate) fn gen_binding_tokens(
+        self,
+        direction: Direction<FromProstFieldInfo, IntoProstFieldInfo>,
+    ) -> darling::Result<TokenStream> {
+        self.gen_member_tokens(direction, true)
+    }
+
+    fn gen_member_tokens(
+        self,
+        direction: Direction<FromProstFieldInfo, IntoProstFieldInfo>,
+        bound: bool,
+    ) -> darling::Result<TokenStream> {
+        let span = self.span();
 
         // We use the same field name as the protobuf field name unless the user
         // specifies a different name via `#[prost(name = "foo"`)]`
         // attribute.
-        let our_name = &self.ident();
-        let proto_name = &self.name.as_ref().unwrap_or(our_name);
+        let our_name = &self.member();
+
+        if self.is_skipped() {
+            // skip this field if it is marked with #[prost(skip)]
+            let tok = match direction {
+                Direction::IntoProst(_) => TokenStream::new(),
+                Direction::FromProst(_) => {
+                    // FromProst: By skipping we initialize with Default value.
+                    quote_spanned! { span =>
+                        #our_name: ::std::default::Default::default(),
+                    }
+                }
+            };
+            return Ok(tok);
+        }
 
+        let proto_name = &self.proto_member()?;
         let dest_field = if direction.is_into() {
             proto_name
         } else {
             our_name
         };
 
-        let mut rhs_value_tok = if direction.is_into() {
+        let input = if direction.is_into() && bound {
+            quote_spanned! { span =>
+                #our_name
+            }
+        } else if direction.is_into() {
             quote_spanned! { span =>
                 value.#our_name
             }
@@ -37,6 +75,79 @@ impl ProstFieldInfo {
             }
         };
 
+        let proto_name_str = proto_name.to_token_stream().to_string();
+        let rhs_value_tok = self.gen_value(direction, input, Some(&proto_name_str))?;
+        Ok(quote_spanned! { span =>
+                #dest_field: #rhs_value_tok,
+        })
+    }
+
+    // Generates the conversion of a `#[prost(transparent)]` field. The input
+    // is the whole value in FromProst.
+    pub(crate) fn gen_transparent(
+        self,
+        direction: Direction<FromProstFieldInfo, IntoProstFieldInfo>,
+    ) -> darling::Result<TokenStream> {
+        let span = self.span();
+        let our_name = &self.member();
+        match direction {
+            Direction::IntoProst(_) => {
+                self.gen_value(direction, quote_spanned! { span => value.#our_name }, None)
+            }
+            Direction::FromProst(_) => {
+                // The whole value is borrowed in `by_ref` conversions.
+                let input = if direction.is_by_ref() {
+                    quote_spanned! { span => (*value) }
+                } else {
+                    quote_spanned! { span => value }
+                };
+                let rhs_value_tok = self.gen_value(direction, input, None)?;
+                Ok(quote_spanned! { span =>
+                    #our_name: #rhs_value_tok,
+                })
+            }
+        }
+    }
+
+    // Generates the expression converting `input` to the destination type.
+    // The `path` (the proto field name) is recorded in errors of fallible
+    // conversions.
+    pub(crate) fn gen_value(
+        &self,
+        direction: Direction<FromProstFieldInfo, IntoProstFieldInfo>,
+        input: TokenStream,
+        path: Option<&str>,
+    ) -> darling::Result<TokenStream> {
+        let direction = self.with_module_mapper(direction)?;
+        if direction.has_user_mapper() {
+            let mapped = ProstFieldInfo {
+                mapped: true,
+                ..self.clone()
+            };
+            return mapped.gen_mapped_value(direction, input, path);
+        }
+        self.gen_mapped_value(direction, input, path)
+    }
+
+    fn gen_mapped_value(
+        &self,
+        direction: Direction<FromProstFieldInfo, IntoProstFieldInfo>,
+        input: TokenStream,
+        path: Option<&str>,
+    ) -> darling::Result<TokenStream> {
+        let span = self.span();
+        let mut rhs_value_tok = input;
+        let (map, map_expr) = match direction {
+            Direction::IntoProst(ref info) => (info.map.as_ref(), info.map_expr.as_ref()),
+            Direction::FromProst(ref info) => (info.map.as_ref(), info.map_expr.as_ref()),
+        };
+        if let (Some(_), Some(map_expr)) = (map, map_expr) {
+            return Err(
+                darling::Error::custom("`map` and `map_expr` are mutually exclusive")
+                    .with_span(map_expr),
+            );
+        }
+
         // How do we map the value?
         // - Option<T>:
         //  - We map the inner value.
@@ -58,41 +169,318 @@ impl ProstFieldInfo {
         //  - FromProst + required: our_name: incoming.unwrap()
         //
         // - always add .into() after mapping.
+        //
+        // In fallible (TryFromProst) mode, `required` fields report a missing
+        // value as an error instead of unwrapping, and `into()` is replaced by
+        // `try_into()` whose error is propagated with `?`.
+        //
+        // A `oneof` field of a bare type is required, the prost field is always
+        // an `Option`.
+        // So is an `any` field, decoding an `Any` can fail so it's only
+        // supported by TryFromProst.
 
         // Primary cases we need to take care of:
-        //  - Skipped
         //  - Option<T>
         //  - Vec<T>
         //  - Everything else
         //
-        if self.is_skipped() {
-            // skip this field if it is marked with #[prost(skip)]
-            let tok = match direction {
-                Direction::IntoProst(_) => TokenStream::new(),
-                Direction::FromProst(_) => {
-                    // FromProst: By skipping we initialize with Default value.
-                    quote_spanned! { span =>
-                        #dest_field: ::std::default::Default::default(),
-                    }
-                }
-            };
-            return Ok(tok);
-        }
-
         let option_type = extract_inner_type_from_container(&self.ty, option_segment);
-        let vec_type = extract_inner_type_from_container(&self.ty, vec_segment);
+        // A `#[prost(bytes)]` `Vec<u8>` is converted as a whole.
+        let vec_type = extract_inner_type_from_container(&self.ty, vec_segment)
+            .filter(|_| !self.is_whole_value(&self.ty));
         // match both HashMap and BTreeMap
         let map_type = extract_inner_type_from_container(&self.ty, map_segment);
         let hashmap_type = extract_inner_type_from_container(&self.ty, hashmap_segment);
+        let fallible = direction.is_fallible();
+        // In `by_ref` conversions, the input is borrowed. Collections are
+        // iterated by reference and leaf values are cloned.
+        let by_ref = direction.is_by_ref();
+
+        if let Direction::FromProst(ref from_field_info) = direction
+            && let Some(ref try_map) = from_field_info.try_map
+        {
+            if !fallible {
+                return Err(
+                    darling::Error::custom("`try_map` is only supported by TryFromProst")
+                        .with_span(try_map),
+                );
+            }
+            if from_field_info.map.is_some() || from_field_info.map_expr.is_some() {
+                return Err(
+                    darling::Error::custom("`map` and `try_map` are mutually exclusive")
+                        .with_span(try_map),
+                );
+            }
+        }
+
+        if let Direction::FromProst(ref from_field_info) = direction
+            && *from_field_info.deny_duplicates
+        {
+            let span = from_field_info.deny_duplicates.span();
+            if !fallible {
+                return Err(darling::Error::custom(
+                    "`deny_duplicates` is only supported by TryFromProst",
+                )
+                .with_span(&span));
+            }
+            if !self.containers().any(|c| c.is_set()) {
+                return Err(darling::Error::custom(
+                    "`deny_duplicates` needs a set field, e.g. `HashSet<T>`",
+                )
+                .with_span(&span));
+            }
+        }
+
+        if let Direction::FromProst(ref from_field_info) = direction
+            && let Some(ref try_map_key) = from_field_info.try_map_key
+        {
+            if !fallible {
+                return Err(darling::Error::custom(
+                    "`try_map_key` is only supported by TryFromProst",
+                )
+                .with_span(try_map_key));
+            }
+            if from_field_info.map_key.is_some() {
+                return Err(darling::Error::custom(
+                    "`map_key` and `try_map_key` are mutually exclusive",
+                )
+                .with_span(try_map_key));
+            }
+        }
+        let key_mapper = match direction {
+            Direction::FromProst(ref info) => info.map_key.as_ref().or(info.try_map_key.as_ref()),
+            Direction::IntoProst(ref info) => info.map_key.as_ref(),
+        };
+        if let Some(key_mapper) = key_mapper
+            && !*self.map_entries
+            && !self.containers().any(|c| {
+                matches!(
+                    c,
+                    Container::Map { .. }
+                        | Container::Collection {
+                            kind: CollectionKind::Map,
+                            ..
+                        }
+                )
+            })
+        {
+            return Err(
+                darling::Error::custom("`map_key` and `try_map_key` need a map field")
+                    .with_span(key_mapper),
+            );
+        }
+
+        if self.oneof.is_some() && (vec_type.is_some() || map_type.is_some()) {
+            return Err(darling::Error::custom(
+                "`oneof` fields must be an enum or an `Option` of an enum",
+            )
+            .with_span(&self.ty));
+        }
+        if self.wrapper.is_some() && option_type.is_none() {
+            return Err(
+                darling::Error::custom("`wrapper` fields must be `Option<T>`").with_span(&self.ty),
+            );
+        }
+        if *self.well_known && *self.bytes {
+            return Err(
+                darling::Error::custom("`well_known` and `bytes` are mutually exclusive")
+                    .with_span(&self.bytes.span()),
+            );
+        }
+        if *self.well_known && !cfg!(feature = "prost-types") {
+            return Err(darling::Error::custom(
+                "`well_known` needs the `prost-types` feature of prost-dto",
+            )
+            .with_span(&self.well_known.span()));
+        }
+        // Converting from a well-known type can fail, e.g. on a negative
+        // `google.protobuf.Duration`.
+        if direction.is_from()
+            && !fallible
+            && self.has_well_known_mapper(&direction)
+            && !self.is_bytes()
+        {
+            return Err(darling::Error::custom(
+                "well-known types are only supported by TryFromProst",
+            )
+            .with_span(&self.ty));
+        }
+        // Converting into an array or a fixed-length bytes type (e.g.
+        // `uuid::Uuid`) fails on a length mismatch.
+        if direction.is_from() && !fallible && self.has_fixed_length(&direction) {
+            return Err(darling::Error::custom(
+                "fixed-length types are only supported by TryFromProst",
+            )
+            .with_span(&self.ty));
+        }
+        if *self.any && direction.is_from() && !fallible {
+            return Err(
+                darling::Error::custom("`any` fields are only supported by TryFromProst")
+                    .with_span(&self.any.span()),
+            );
+        }
+        let required = self.required || self.oneof.is_some() || *self.any;
+
+        // Adds the field to the path of errors bubbling up from the inner
+        // conversion.
+        let propagate_with_path = |result_tok: TokenStream| match path {
+            Some(path) => quote_spanned! { span =>
+                #result_tok.map_err(|e| e.at_field(#path))?
+            },
+            None => quote_spanned! { span => #result_tok? },
+        };
+        let missing_required = match path {
+            Some(path) if self.oneof.is_some() => quote_spanned! { span =>
+                ::prost_dto::ConversionError::missing_oneof(#path)
+            },
+            Some(path) => quote_spanned! { span =>
+                ::prost_dto::ConversionError::missing_required(#path)
+            },
+            None if self.oneof.is_some() => quote_spanned! { span =>
+                ::prost_dto::ConversionError::new(::prost_dto::ErrorKind::MissingOneof)
+            },
+            None => quote_spanned! { span =>
+                ::prost_dto::ConversionError::new(::prost_dto::ErrorKind::MissingRequired)
+            },
+        };
+
+        // A map field converted into a `Vec` of entries, in the iteration order
+        // of the prost map.
+        if *self.map_entries {
+            let converted = self.gen_map_entries(&direction, rhs_value_tok)?;
+            return Ok(if fallible {
+                propagate_with_path(converted)
+            } else {
+                converted
+            });
+        }
+
+        // Containers of containers, e.g. `Option<Vec<T>>`, and smart pointers
+        // are converted layer by layer.
+        if self.is_layered() {
+            if let Direction::FromProst(ref info) = direction
+                && info.always_none
+            {
+                return Ok(quote_spanned! { span => None });
+            }
+            // A required message is only unwrapped into a bare or pointer field.
+            let unwraps_required =
+                self.required && self.container(&self.ty).is_none_or(|c| c.is_pointer());
+            if (self.required && !unwraps_required)
+                || *self.any
+                || self.wrapper.is_some()
+                || self.enumeration.is_some()
+                || self.oneof.is_some()
+            {
+                return Err(darling::Error::custom(
+                    "`required`, `wrapper`, `enumeration`, `oneof` and `any` are not \
+                     supported by nested containers",
+                )
+                .with_span(&self.ty));
+            }
+            // Nested containers are borrowed in `by_ref` conversions.
+            if unwraps_required && direction.is_from() {
+                let as_ref = if by_ref {
+                    quote_spanned! { span => .as_ref() }
+                } else {
+                    quote! {}
+                };
+                rhs_value_tok = if fallible {
+                    quote_spanned! { span =>
+                        #rhs_value_tok #as_ref.ok_or_else(|| #missing_required)?
+                    }
+                } else {
+                    quote_spanned! { span => #rhs_value_tok #as_ref.unwrap() }
+                };
+            } else if by_ref {
+                rhs_value_tok = quote_spanned! { span => &#rhs_value_tok };
+            }
+            let converted = self.gen_nested_value(&direction, &self.ty, rhs_value_tok, by_ref);
+            return Ok(if fallible {
+                propagate_with_path(converted)
+            } else if unwraps_required && direction.is_into() {
+                quote_spanned! { span => Some(#converted) }
+            } else {
+                converted
+            });
+        }
+        if let Some(ref enumeration) = self.enumeration {
+            if map_type.is_some() {
+                return Err(darling::Error::custom(
+                    "`enumeration` fields must be `T`, `Option<T>` or `Vec<T>`",
+                )
+                .with_span(enumeration));
+            }
+            if self
+                .wrap_with_mapper(direction.clone(), quote! {})
+                .is_some()
+            {
+                return Err(darling::Error::custom(
+                    "`enumeration` fields can't be mapped with `map` or `try_map`",
+                )
+                .with_span(enumeration));
+            }
+            // Unknown values can't be converted.
+            if direction.is_from() && !fallible {
+                return Err(darling::Error::custom(
+                    "`enumeration` fields are only supported by TryFromProst",
+                )
+                .with_span(enumeration));
+            }
+            // The i32 values are cheap to clone.
+            if by_ref {
+                rhs_value_tok =
+                    quote_spanned! { span => ::std::clone::Clone::clone(&#rhs_value_tok) };
+            }
+            return Ok(self.gen_enumeration(
+                &direction,
+                rhs_value_tok,
+                enumeration,
+                option_type,
+                vec_type.is_some(),
+                propagate_with_path,
+            ));
+        }
 
         // 1. Do we need to unwrap the input before processing? We do that if
         // the field is `required` and our local type is not `Option<T>` when
         // converting from prost to rust.
-        if option_type.is_none() && direction.is_from() && self.required {
-            rhs_value_tok = quote_spanned! { span =>
-                #rhs_value_tok.unwrap()
+        // The unwrapped input is a reference in `by_ref` conversions.
+        let mut is_borrowed = false;
+        if option_type.is_none() && direction.is_from() && required {
+            let as_ref = if by_ref {
+                quote_spanned! { span => .as_ref() }
+            } else {
+                quote! {}
+            };
+            is_borrowed = by_ref;
+            rhs_value_tok = if fallible {
+                quote_spanned! { span =>
+                    #rhs_value_tok #as_ref.ok_or_else(|| #missing_required)?
+                }
+            } else {
+                quote_spanned! { span =>
+                    #rhs_value_tok #as_ref.unwrap()
+                }
             };
         }
+        // The input of the conversion of inner values, `v` is borrowed in
+        // `by_ref` conversions.
+        let v_input = if by_ref {
+            self.gen_ref_input(&direction, quote! { v })
+        } else {
+            quote! { v }
+        };
+        let into_mapper = if by_ref {
+            quote_spanned! { span => |v| ::std::convert::Into::into(#v_input) }
+        } else {
+            quote_spanned! { span => Into::into }
+        };
+        let iter = if by_ref {
+            quote_spanned! { span => iter }
+        } else {
+            quote_spanned! { span => into_iter }
+        };
 
         if let Some(_inner_ty) = option_type {
             // Option<T>
@@ -111,28 +499,69 @@ impl ProstFieldInfo {
                     };
                 } else if self.required {
                     let span = self.required.span();
+                    let borrow = if by_ref {
+                        quote! { & }
+                    } else {
+                        quote! {}
+                    };
+                    is_borrowed = by_ref;
                     rhs_value_tok = quote_spanned! { span =>
-                        Some(#rhs_value_tok)
+                        Some(#borrow #rhs_value_tok)
                     };
                 }
             }
+            if by_ref && !is_set_to_none && !is_borrowed {
+                rhs_value_tok = quote_spanned! { span => #rhs_value_tok.as_ref() };
+            }
 
-            let mapper = self
-                .wrap_with_mapper(direction.clone(), quote! { v })
-                .map(|mapper| {
-                    quote_spanned! { span =>
-                        |v| #mapper
-                    }
-                })
-                // If there is no mapper, we just map the inner value with any
-                // existing Into impl.
-                .unwrap_or_else(|| {
-                    quote_spanned! { span =>
-                        Into::into
-                    }
-                });
+            // Wrapper messages (e.g. `google.protobuf.StringValue`) hold the
+            // inner value in their `value` field.
+            let inner_input = match self.wrapper {
+                Some(_) if direction.is_from() && by_ref => {
+                    self.gen_ref_input(&direction, quote_spanned! { span => &v.value })
+                }
+                Some(_) if direction.is_from() => quote_spanned! { span => v.value },
+                _ => v_input.clone(),
+            };
 
-            if !is_set_to_none {
+            if is_set_to_none {
+                // Nothing to map.
+            } else if let Some(try_convert) = self.gen_try_convert(&direction, inner_input.clone())
+            {
+                rhs_value_tok = propagate_with_path(quote_spanned! { span =>
+                    #rhs_value_tok.map(|v| #try_convert).transpose()
+                });
+            } else if let Some(ref wrapper) = self.wrapper {
+                let converted = self
+                    .wrap_with_mapper(direction.clone(), inner_input.clone())
+                    .unwrap_or_else(|| quote_spanned! { span => #inner_input.into() });
+                let mapper = match (wrapper, direction.is_into()) {
+                    (_, false) => quote_spanned! { span => |v| #converted },
+                    (Override::Explicit(wrapper_path), true) => quote_spanned! { span =>
+                        |v| #wrapper_path { value: #converted }
+                    },
+                    (Override::Inherit, true) => {
+                        return Err(darling::Error::custom(
+                            "IntoProst needs the wrapper message, e.g. \
+                             #[prost(wrapper = \"proto::StringValue\")]",
+                        )
+                        .with_span(&self.ty));
+                    }
+                };
+                rhs_value_tok = quote_spanned! { span =>
+                    #rhs_value_tok.map(#mapper)
+                };
+            } else {
+                let mapper = self
+                    .wrap_with_mapper(direction.clone(), v_input.clone())
+                    .map(|mapper| {
+                        quote_spanned! { span =>
+                            |v| #mapper
+                        }
+                    })
+                    // If there is no mapper, we just map the inner value with any
+                    // existing Into impl.
+                    .unwrap_or_else(|| into_mapper.clone());
                 rhs_value_tok = quote_spanned! { span =>
                     #rhs_value_tok.map(#mapper)
                 };
@@ -146,64 +575,750 @@ impl ProstFieldInfo {
             }
         } else if let Some(_inner_ty) = vec_type {
             // A Vec<T>
-            let mapper = self
-                .wrap_with_mapper(direction, quote! { v })
-                .map(|mapper| {
+            if let Some(try_convert) = self.gen_try_convert(&direction, v_input.clone()) {
+                rhs_value_tok = propagate_with_path(quote_spanned! { span =>
+                    #rhs_value_tok.#iter().enumerate()
+                        .map(|(i, v)| #try_convert.map_err(|e| e.at_index(i)))
+                        .collect::<::std::result::Result<::std::vec::Vec<_>, ::prost_dto::ConversionError>>()
+                });
+            } else {
+                let mapper = self
+                    .wrap_with_mapper(direction.clone(), v_input.clone())
+                    .map(|mapper| {
+                        quote_spanned! { span =>
+                                |v| #mapper
+                        }
+                    })
+                    // If there is no mapper, we just map the inner value with any
+                    // existing Into impl.
+                    .unwrap_or_else(|| into_mapper.clone());
+                rhs_value_tok = quote_spanned! { span =>
+                    #rhs_value_tok.#iter().map(#mapper).collect::<::std::vec::Vec<_>>()
+                };
+            }
+        } else if let Some(_inner_ty) = map_type {
+            // A HashMap<K,V>
+            let collection = if hashmap_type.is_some() {
+                quote_spanned! { span => ::std::collections::HashMap<_, _> }
+            } else {
+                quote_spanned! { span => ::std::collections::BTreeMap<_, _> }
+            };
+            let pair = |key, value| quote_spanned! { span => (#key, #value) };
+            if let Some(try_convert) = self.gen_try_convert(&direction, v_input.clone()) {
+                let entry = self.gen_map_entry(&direction, by_ref, try_convert, pair);
+                rhs_value_tok = propagate_with_path(quote_spanned! { span =>
+                    #rhs_value_tok.#iter()
+                        .map(|(k, v)| #entry)
+                        .collect::<::std::result::Result<#collection, ::prost_dto::ConversionError>>()
+                });
+            } else {
+                let value = self
+                    .wrap_with_mapper(direction.clone(), v_input.clone())
+                    // If there is no mapper, we just map the inner value with any
+                    // existing Into impl.
+                    .unwrap_or_else(|| quote_spanned! { span => #v_input.into() });
+                let entry = self.gen_map_entry(&direction, by_ref, value, pair);
+                rhs_value_tok = quote_spanned! { span =>
+                    #rhs_value_tok.#iter().map(|(k, v)| #entry).collect::<#collection>()
+                };
+            }
+        } else if fallible {
+            // Bare type, we try_into() after mapping.
+            if by_ref {
+                rhs_value_tok = self.gen_bare_ref_input(&direction, rhs_value_tok, is_borrowed);
+            }
+            let mapped = self.wrap_with_mapper(direction.clone(), rhs_value_tok.clone());
+            let try_into = quote_spanned! { span => ::std::convert::TryInto::try_into };
+            let from_err = quote_spanned! { span => ::prost_dto::ConversionError::from };
+            rhs_value_tok = match mapped {
+                // `with` modules produce the destination type, they can be
+                // generic over it.
+                Some(mapped) if self.with.is_some() => quote_spanned! { span =>
+                    ::std::result::Result::<_, ::prost_dto::ConversionError>::Ok(#mapped)
+                },
+                Some(mapped) if self.try_with.is_some() => quote_spanned! { span =>
+                    #mapped.map_err(::prost_dto::ConversionError::mapper)
+                },
+                Some(mapped) if self.has_well_known_mapper(&direction) => mapped,
+                Some(mapped) if self.has_try_mapper(&direction) => quote_spanned! { span =>
+                    #mapped.map_err(::prost_dto::ConversionError::mapper)
+                        .and_then(|v| #try_into(v).map_err(#from_err))
+                },
+                Some(mapped) => quote_spanned! { span => #try_into(#mapped).map_err(#from_err) },
+                None => quote_spanned! { span => #try_into(#rhs_value_tok).map_err(#from_err) },
+            };
+            rhs_value_tok = propagate_with_path(rhs_value_tok);
+        } else {
+            // Bare type
+            if by_ref {
+                rhs_value_tok = self.gen_bare_ref_input(&direction, rhs_value_tok, is_borrowed);
+            }
+            rhs_value_tok = self
+                .wrap_with_mapper(direction.clone(), quote_spanned! { span => #rhs_value_tok })
+                .unwrap_or(rhs_value_tok);
+            // We need to .into(), unless the built-in mapper or a `with` module
+            // already produced the destination type (which can't be inferred
+            // otherwise).
+            // A borrowed input needs the function call syntax, `&a.b.into()`
+            // would borrow the result.
+            let well_known = self.has_well_known_mapper(&direction)
+                || self.with.is_some()
+                || self.try_with.is_some();
+            if !well_known && by_ref {
+                rhs_value_tok =
+                    quote_spanned! { span => ::std::convert::Into::into(#rhs_value_tok) };
+            } else if !well_known {
+                rhs_value_tok = quote_spanned! { span => #rhs_value_tok.into() };
+            }
+
+            if required && direction.is_into() {
+                rhs_value_tok = quote_spanned! { span => Some(#rhs_value_tok) };
+            }
+        };
+
+        Ok(rhs_value_tok)
+    }
+
+    // The container of `ty`. The type of a `#[prost(collection)]` field, below
+    // its `Option` and smart pointers, is a collection. Arrays are containers
+    // unless mapped, values converted as a whole aren't.
+    fn container<'a>(&'a self, ty: &'a syn::Type) -> Option<Container<'a>> {
+        if self.is_whole_value(ty) {
+            return None;
+        }
+        // Arrays are converted as a whole by user mappers.
+        if !self.mapped
+            && let syn::Type::Array(ref array) = *ty
+        {
+            return Some(Container::Array(&array.elem));
+        }
+        if let Some(container) = Container::of(ty) {
+            return Some(container);
+        }
+        if !self.collection {
+            return None;
+        }
+        let mut outer = &self.ty;
+        while let Some(container) = Container::of(outer)
+            .filter(|c| matches!(c, Container::Option(_) | Container::Pointer(..)))
+        {
+            outer = container.inner();
+        }
+        if std::ptr::eq(outer, ty) {
+            Container::collection(ty, true)
+        } else {
+            None
+        }
+    }
+
+    // The containers of the field from the outermost.
+    fn containers(&self) -> impl Iterator<Item = Container<'_>> {
+        std::iter::successors(self.container(&self.ty), |c| self.container(c.inner()))
+    }
+
+    // Whether the field is converted layer by layer by `gen_nested_value`,
+    // `Vec` and maps of the standard library are converted directly.
+    fn is_layered(&self) -> bool {
+        let mut depth = 0;
+        for container in self.containers() {
+            if matches!(
+                container,
+                Container::Pointer(..) | Container::Collection { .. } | Container::Array(_)
+            ) {
+                return true;
+            }
+            depth += 1;
+        }
+        self.boxed || depth > 1
+    }
+
+    // Converts a prost map from/into a `Vec` of `(key, value)` tuples or of
+    // entries with `key` and `value` fields, e.g. `Vec<Entry>`.
+    fn gen_map_entries(
+        &self,
+        direction: &Direction<FromProstFieldInfo, IntoProstFieldInfo>,
+        mut input: TokenStream,
+    ) -> darling::Result<TokenStream> {
+        let span = self.span();
+        let entries_error = || {
+            darling::Error::custom("`map_entries` fields must be `Vec<(K, V)>` or `Vec<Entry>`")
+                .with_span(&self.map_entries.span())
+        };
+        let element =
+            extract_inner_type_from_container(&self.ty, vec_segment).ok_or_else(entries_error)?;
+        if self.required
+            || *self.any
+            || self.wrapper.is_some()
+            || self.enumeration.is_some()
+            || self.oneof.is_some()
+        {
+            return Err(darling::Error::custom(
+                "`required`, `wrapper`, `enumeration`, `oneof` and `any` are not \
+                 supported by `map_entries`",
+            )
+            .with_span(&self.ty));
+        }
+        // The value type of an entry struct is unknown, it's converted as is.
+        let (pattern, make_entry, value_ty): (_, Box<dyn Fn(_, _) -> _>, _) = match *element {
+            syn::Type::Tuple(ref tuple) if tuple.elems.len() == 2 => (
+                quote_spanned! { span => (k, v) },
+                Box::new(|key, value| quote_spanned! { span => (#key, #value) }),
+                &tuple.elems[1],
+            ),
+            syn::Type::Path(ref entry) if entry.qself.is_none() => {
+                // Generic arguments need the turbofish in expressions.
+                let mut entry = entry.path.clone();
+                if let Some(segment) = entry.segments.last_mut()
+                    && let syn::PathArguments::AngleBracketed(ref mut args) = segment.arguments
+                {
+                    args.colon2_token = Some(Default::default());
+                }
+                (
+                    quote_spanned! { span => #entry { key: k, value: v, .. } },
+                    Box::new(move |key, value| {
+                        quote_spanned! { span => #entry { key: #key, value: #value } }
+                    }),
+                    element,
+                )
+            }
+            _ => return Err(entries_error()),
+        };
+        // Entries are iterated by reference in `by_ref` conversions.
+        let by_ref = direction.is_by_ref();
+        if by_ref {
+            input = quote_spanned! { span => ::std::iter::IntoIterator::into_iter(&#input) };
+        } else {
+            input = quote_spanned! { span => #input.into_iter() };
+        }
+        let value = self.gen_nested_value(direction, value_ty, quote! { v }, by_ref);
+        Ok(match (direction.is_into(), direction.is_fallible()) {
+            (true, _) => {
+                let entry = self.gen_map_entry(direction, by_ref, value, |key, value| {
+                    quote_spanned! { span => (#key, #value) }
+                });
+                quote_spanned! { span =>
+                    #input.map(|#pattern| #entry).collect()
+                }
+            }
+            (false, true) => {
+                let entry = self.gen_map_entry(direction, by_ref, value, make_entry);
+                quote_spanned! { span =>
+                    #input
+                        .map(|(k, v)| #entry)
+                        .collect::<::std::result::Result<::std::vec::Vec<_>, ::prost_dto::ConversionError>>()
+                }
+            }
+            (false, false) => {
+                let entry = self.gen_map_entry(direction, by_ref, value, make_entry);
+                quote_spanned! { span =>
+                    #input.map(|(k, v)| #entry).collect::<::std::vec::Vec<_>>()
+                }
+            }
+        })
+    }
+
+    // Generates the entry of a map from its key `k` and converted `value`,
+    // `make_entry` builds the entry from the converted key and value. The key
+    // is borrowed in `by_ref` conversions. In fallible mode `value` and the
+    // entry are `Result`s, errors are located at the key.
+    fn gen_map_entry(
+        &self,
+        direction: &Direction<FromProstFieldInfo, IntoProstFieldInfo>,
+        key_is_ref: bool,
+        value: TokenStream,
+        make_entry: impl Fn(TokenStream, TokenStream) -> TokenStream,
+    ) -> TokenStream {
+        let span = self.span();
+        let key = if key_is_ref {
+            quote_spanned! { span => ::std::clone::Clone::clone(k) }
+        } else {
+            quote_spanned! { span => k }
+        };
+        let converted_key = match direction.map_key() {
+            Some(map_key) => quote_spanned! { span => #map_key(#key) },
+            None => quote_spanned! { span => #key.into() },
+        };
+        if !direction.is_fallible() {
+            return make_entry(converted_key, value);
+        }
+        let ok = match direction {
+            Direction::FromProst(FromProstFieldInfo {
+                try_map_key: Some(try_map_key),
+                ..
+            }) => {
+                // The key is still needed to locate errors.
+                let key = if key_is_ref {
+                    key
+                } else {
+                    quote_spanned! { span => ::std::clone::Clone::clone(&k) }
+                };
+                let entry = make_entry(quote_spanned! { span => key }, quote! { v });
+                quote_spanned! { span =>
+                    #try_map_key(#key)
+                        .map(|key| #entry)
+                        .map_err(|e| ::prost_dto::ConversionError::mapper(e).at_key(&k))
+                }
+            }
+            _ => {
+                let entry = make_entry(converted_key, quote! { v });
+                quote_spanned! { span => ::std::result::Result::Ok(#entry) }
+            }
+        };
+        quote_spanned! { span =>
+            match #value {
+                ::std::result::Result::Ok(v) => #ok,
+                ::std::result::Result::Err(e) => ::std::result::Result::Err(e.at_key(&k)),
+            }
+        }
+    }
+
+    // Converts `input` of a (possibly nested) container type `ty`, the mapper
+    // applies to the innermost values. An `Option` of a repeated or map field
+    // is flattened, as prost has no `Option` around them: `None` is empty.
+    // Returns a `Result` in fallible mode. A `borrowed` input is a reference,
+    // its containers are iterated by reference and its leaf values cloned.
+    fn gen_nested_value(
+        &self,
+        direction: &Direction<FromProstFieldInfo, IntoProstFieldInfo>,
+        ty: &syn::Type,
+        input: TokenStream,
+        borrowed: bool,
+    ) -> TokenStream {
+        let span = self.span();
+        let fallible = direction.is_fallible();
+        let Some(container) = self.container(ty) else {
+            // A boxed prost message is unboxed before converting it.
+            let input = match (self.boxed && direction.is_from(), borrowed) {
+                (true, true) => quote_spanned! { span => &**#input },
+                (true, false) => quote_spanned! { span => *#input },
+                (false, _) => input,
+            };
+            let input = if borrowed {
+                self.gen_ref_input(direction, input)
+            } else {
+                input
+            };
+            let converted = match self.gen_try_convert(direction, input.clone()) {
+                Some(try_convert) => try_convert,
+                None => {
+                    let converted = self
+                        .wrap_with_mapper(direction.clone(), input.clone())
+                        .unwrap_or_else(
+                            || quote_spanned! { span => ::std::convert::Into::into(#input) },
+                        );
+                    if fallible {
+                        quote_spanned! { span =>
+                            ::std::result::Result::<_, ::prost_dto::ConversionError>::Ok(#converted)
+                        }
+                    } else {
+                        converted
+                    }
+                }
+            };
+            return if self.boxed && direction.is_into() {
+                quote_spanned! { span => ::std::boxed::Box::new(#converted) }
+            } else {
+                converted
+            };
+        };
+        // Smart pointers are unwrapped before converting their value into
+        // prost, and wrap the value converted from prost.
+        if let Container::Pointer(pointer, inner_ty) = container {
+            if direction.is_into() && borrowed {
+                // The value is borrowed through the pointer, a `Cow` may not
+                // own a value we could clone.
+                return match pointer {
+                    SmartPointer::Cow => self.gen_nested_value(
+                        direction,
+                        inner_ty,
+                        quote_spanned! { span =>
+                            ::std::borrow::Cow::into_owned(::std::clone::Clone::clone(#input))
+                        },
+                        false,
+                    ),
+                    _ => self.gen_nested_value(
+                        direction,
+                        inner_ty,
+                        quote_spanned! { span => &**#input },
+                        true,
+                    ),
+                };
+            }
+            if direction.is_into() {
+                let unwrapped = match pointer {
+                    SmartPointer::Box => quote_spanned! { span => *#input },
+                    SmartPointer::Arc => quote_spanned! { span =>
+                        ::std::sync::Arc::unwrap_or_clone(#input)
+                    },
+                    SmartPointer::Rc => quote_spanned! { span =>
+                        ::std::rc::Rc::unwrap_or_clone(#input)
+                    },
+                    SmartPointer::Cow => quote_spanned! { span =>
+                        ::std::borrow::Cow::into_owned(#input)
+                    },
+                };
+                return self.gen_nested_value(direction, inner_ty, unwrapped, false);
+            }
+            let wrap = match pointer {
+                SmartPointer::Box => quote_spanned! { span => ::std::boxed::Box::new },
+                SmartPointer::Arc => quote_spanned! { span => ::std::sync::Arc::new },
+                SmartPointer::Rc => quote_spanned! { span => ::std::rc::Rc::new },
+                SmartPointer::Cow => quote_spanned! { span => ::std::borrow::Cow::Owned },
+            };
+            let inner = self.gen_nested_value(direction, inner_ty, input, borrowed);
+            return if fallible {
+                quote_spanned! { span => #inner.map(#wrap) }
+            } else {
+                quote_spanned! { span => #wrap(#inner) }
+            };
+        }
+        let inner = self.gen_nested_value(direction, container.inner(), quote! { v }, borrowed);
+        // Iterates `input`, by reference if it's borrowed.
+        let iter = if borrowed {
+            quote_spanned! { span => ::std::iter::IntoIterator::into_iter(#input) }
+        } else {
+            quote_spanned! { span => #input.into_iter() }
+        };
+        // `Option::map` over a reference to the value if it's borrowed.
+        let option = if borrowed {
+            quote_spanned! { span => ::std::option::Option::as_ref(#input) }
+        } else {
+            input.clone()
+        };
+        match container {
+            Container::Option(inner_ty)
+                if self.container(inner_ty).is_some_and(|c| c.is_collection()) =>
+            {
+                match (direction.is_into(), fallible) {
+                    (true, _) => quote_spanned! { span =>
+                        #option.map(|v| #inner).unwrap_or_default()
+                    },
+                    (false, true) => quote_spanned! { span =>
+                        ::std::option::Option::Some(#input)
+                            .filter(|v| !v.is_empty())
+                            .map(|v| #inner)
+                            .transpose()
+                    },
+                    (false, false) => quote_spanned! { span =>
+                        ::std::option::Option::Some(#input)
+                            .filter(|v| !v.is_empty())
+                            .map(|v| #inner)
+                    },
+                }
+            }
+            Container::Option(_) if fallible => quote_spanned! { span =>
+                #option.map(|v| #inner).transpose()
+            },
+            Container::Option(_) => quote_spanned! { span =>
+                #option.map(|v| #inner)
+            },
+            Container::Map { .. }
+            | Container::Collection {
+                kind: CollectionKind::Map,
+                ..
+            } => {
+                let collection = container.collect_type(direction.is_into(), span);
+                let entry = self.gen_map_entry(direction, borrowed, inner, |key, value| {
+                    quote_spanned! { span => (#key, #value) }
+                });
+                if fallible {
                     quote_spanned! { span =>
-                            |v| #mapper
+                        #iter
+                            .map(|(k, v)| #entry)
+                            .collect::<::std::result::Result<#collection, ::prost_dto::ConversionError>>()
                     }
-                })
-                // If there is no mapper, we just map the inner value with any
-                // existing Into impl.
-                .unwrap_or_else(|| {
+                } else {
                     quote_spanned! { span =>
-                        Into::into
+                        #iter.map(|(k, v)| #entry).collect::<#collection>()
                     }
-                });
-            rhs_value_tok = quote_spanned! { span =>
-                #rhs_value_tok.into_iter().map(#mapper).collect::<::std::vec::Vec<_>>()
-            };
-        } else if let Some(_inner_ty) = map_type {
-            // A HashMap<K,V>
-            let mapper = self
-                .wrap_with_mapper(direction, quote! { v })
-                .map(|mapper| {
+                }
+            }
+            // A set denying duplicates fails on the first value it already
+            // holds.
+            Container::Collection {
+                kind: CollectionKind::Set,
+                ty,
+                ..
+            } if self.denies_duplicates(direction) => {
+                quote_spanned! { span =>
+                    #iter.enumerate().try_fold(
+                        <#ty as ::std::default::Default>::default(),
+                        |mut set, (i, v)| {
+                            let v = #inner.map_err(|e| e.at_index(i))?;
+                            let len = set.len();
+                            ::std::iter::Extend::extend(&mut set, ::std::iter::once(v));
+                            if set.len() == len {
+                                ::std::result::Result::Err(
+                                    ::prost_dto::ConversionError::duplicate_value().at_index(i),
+                                )
+                            } else {
+                                ::std::result::Result::Ok(set)
+                            }
+                        },
+                    )
+                }
+            }
+            // Arrays are collected into a `Vec` first, whose length is checked.
+            Container::Array(_) if direction.is_into() => quote_spanned! { span =>
+                ::std::iter::IntoIterator::into_iter(#input)
+                    .map(|v| #inner)
+                    .collect::<::std::vec::Vec<_>>()
+            },
+            // Only TryFromProst converts into arrays.
+            Container::Array(_) => quote_spanned! { span =>
+                #iter.enumerate()
+                    .map(|(i, v)| #inner.map_err(|e| e.at_index(i)))
+                    .collect::<::std::result::Result<::std::vec::Vec<_>, ::prost_dto::ConversionError>>()
+                    .and_then(::prost_dto::array_from_vec)
+            },
+            Container::Vec(_) | Container::Collection { .. } => {
+                let collection = container.collect_type(direction.is_into(), span);
+                if fallible {
                     quote_spanned! { span =>
-                            |(k, v)| (k.into(), #mapper)
+                        #iter.enumerate()
+                            .map(|(i, v)| #inner.map_err(|e| e.at_index(i)))
+                            .collect::<::std::result::Result<#collection, ::prost_dto::ConversionError>>()
                     }
-                })
-                // If there is no mapper, we just map the inner value with any
-                // existing Into impl.
-                .unwrap_or_else(|| {
+                } else {
                     quote_spanned! { span =>
-                        |(k, v)| (k.into(), v.into())
+                        #iter.map(|v| #inner).collect::<#collection>()
                     }
-                });
-            rhs_value_tok = if hashmap_type.is_some() {
+                }
+            }
+            Container::Pointer(..) => unreachable!("smart pointers are converted above"),
+        }
+    }
+
+    // Generates the conversion of an `#[prost(enumeration = "...")]` field,
+    // the prost field holds the i32 repr of the enumeration. In FromProst, we
+    // convert through the typed enumeration and unknown values are reported as
+    // errors. `Option<T>` fields convert with `ProstEnumeration` instead,
+    // where the sentinel is `None`, and so are unknown values in FromProst.
+    fn gen_enumeration(
+        &self,
+        direction: &Direction<FromProstFieldInfo, IntoProstFieldInfo>,
+        input: TokenStream,
+        enumeration: &syn::Path,
+        option_type: Option<&syn::Type>,
+        is_vec: bool,
+        propagate_with_path: impl Fn(TokenStream) -> TokenStream,
+    ) -> TokenStream {
+        let span = enumeration.span();
+        let enumeration_str = darling::util::path_to_string(enumeration);
+        // Only TryFromProst converts from prost.
+        let convert = |v: TokenStream| match direction {
+            Direction::IntoProst(_) => quote_spanned! { span =>
+                ::std::convert::Into::<i32>::into(#v)
+            },
+            Direction::FromProst(_) => quote_spanned! { span =>
+                <#enumeration as ::std::convert::TryFrom<i32>>::try_from(#v)
+                    .map_err(|_| ::prost_dto::ConversionError::unknown_enum_value(#enumeration_str, #v))
+                    .and_then(|e| ::std::convert::TryInto::try_into(e).map_err(::prost_dto::ConversionError::from))
+            },
+        };
+        let converted = convert(quote! { v });
+
+        if let Some(option_type) = option_type {
+            // Proto3 enumeration fields are `i32`, or `Option<i32>` if they
+            // are `optional`.
+            if direction.is_into() {
                 quote_spanned! { span =>
-                #rhs_value_tok.into_iter().map(#mapper).collect::<::std::collections::HashMap<_, _>>()
+                    ::prost_dto::EnumerationRepr::from_option(#input.map(|v| #converted))
                 }
             } else {
+                propagate_with_path(quote_spanned! { span =>
+                    ::prost_dto::EnumerationRepr::into_option(#input)
+                        .map(<#option_type as ::prost_dto::ProstEnumeration>::try_from_i32_or_none)
+                        .transpose()
+                        .map(::std::option::Option::flatten)
+                })
+            }
+        } else if is_vec {
+            if direction.is_into() {
                 quote_spanned! { span =>
-                    #rhs_value_tok.into_iter().map(#mapper).collect::<::std::collections::BTreeMap<_, _>>()
+                    #input.into_iter().map(|v| #converted).collect::<::std::vec::Vec<_>>()
                 }
-            };
+            } else {
+                propagate_with_path(quote_spanned! { span =>
+                    #input.into_iter().enumerate()
+                        .map(|(i, v)| #converted.map_err(|e| e.at_index(i)))
+                        .collect::<::std::result::Result<::std::vec::Vec<_>, ::prost_dto::ConversionError>>()
+                })
+            }
+        } else if direction.is_into() {
+            convert(input)
         } else {
-            // Bare type
-            rhs_value_tok = self
-                .wrap_with_mapper(direction.clone(), quote_spanned! { span => #rhs_value_tok })
-                .unwrap_or(rhs_value_tok);
-            // We need to .into()
-            rhs_value_tok = quote_spanned! { span => #rhs_value_tok.into() };
+            propagate_with_path(convert(input))
+        }
+    }
 
-            if self.required && direction.is_into() {
-                rhs_value_tok = quote_spanned! { span => Some(#rhs_value_tok) };
+    // In `by_ref` conversions, the owned input converting the borrowed value
+    // `v`. Values are cloned unless they're passed by reference to a
+    // `map_by_ref` mapper or the field converts from a reference.
+    fn gen_ref_input(
+        &self,
+        direction: &Direction<FromProstFieldInfo, IntoProstFieldInfo>,
+        v: TokenStream,
+    ) -> TokenStream {
+        let span = self.span();
+        let has_mapper = direction.has_user_mapper();
+        let map_by_ref = match direction {
+            Direction::IntoProst(info) => *info.map_by_ref,
+            Direction::FromProst(info) => *info.map_by_ref,
+        };
+        if has_mapper && map_by_ref {
+            // The mapper borrows it again.
+            quote_spanned! { span => (*#v) }
+        } else if self.by_ref && !has_mapper {
+            v
+        } else {
+            quote_spanned! { span => ::std::clone::Clone::clone(#v) }
+        }
+    }
+
+    // Like `gen_ref_input` for a bare field, `input` is the field itself
+    // unless it was already borrowed by unwrapping.
+    fn gen_bare_ref_input(
+        &self,
+        direction: &Direction<FromProstFieldInfo, IntoProstFieldInfo>,
+        input: TokenStream,
+        is_borrowed: bool,
+    ) -> TokenStream {
+        let span = self.span();
+        if is_borrowed {
+            self.gen_ref_input(direction, input)
+        } else {
+            self.gen_ref_input(direction, quote_spanned! { span => &#input })
+        }
+    }
+
+    // Sets the mapper of `#[prost(with = "module")]`, `module::into_prost` or
+    // `module::from_prost` depending on the direction. The `from_prost` of a
+    // `try_with` module is a `try_map`.
+    fn with_module_mapper(
+        &self,
+        mut direction: Direction<FromProstFieldInfo, IntoProstFieldInfo>,
+    ) -> darling::Result<Direction<FromProstFieldInfo, IntoProstFieldInfo>> {
+        let (module, fallible) = match (&self.with, &self.try_with) {
+            (Some(_), Some(try_with)) => {
+                return Err(
+                    darling::Error::custom("`with` and `try_with` are mutually exclusive")
+                        .with_span(try_with),
+                );
             }
+            (Some(module), None) => (module, false),
+            (None, Some(module)) => (module, true),
+            (None, None) => return Ok(direction),
         };
+        let attr = if fallible { "try_with" } else { "with" };
+        if direction.has_user_mapper() {
+            return Err(darling::Error::custom(format!(
+                "`{attr}` can't be combined with `map`, `map_expr` or `try_map`"
+            ))
+            .with_span(module));
+        }
+        if fallible && direction.is_from() && !direction.is_fallible() {
+            return Err(
+                darling::Error::custom("`try_with` is only supported by TryFromProst")
+                    .with_span(module),
+            );
+        }
+        let (mapper, name) = match direction {
+            Direction::IntoProst(ref mut info) => (&mut info.map, "into_prost"),
+            Direction::FromProst(ref mut info) if fallible => (&mut info.try_map, "from_prost"),
+            Direction::FromProst(ref mut info) => (&mut info.map, "from_prost"),
+        };
+        let mut path = module.clone();
+        path.segments
+            .push(syn::Ident::new(name, module.span()).into());
+        *mapper = Some(path);
+        Ok(direction)
+    }
 
-        Ok(quote_spanned! { span =>
-                #dest_field: #rhs_value_tok,
-        })
+    fn denies_duplicates(
+        &self,
+        direction: &Direction<FromProstFieldInfo, IntoProstFieldInfo>,
+    ) -> bool {
+        matches!(direction, Direction::FromProst(info) if *info.deny_duplicates)
+    }
+
+    fn has_try_mapper(
+        &self,
+        direction: &Direction<FromProstFieldInfo, IntoProstFieldInfo>,
+    ) -> bool {
+        matches!(direction, Direction::FromProst(info) if info.try_map.is_some())
+    }
+
+    // Whether the value is converted by the built-in `prost_dto::WellKnown`
+    // or `prost_dto::ProstBytes` mappers. User-supplied mappers take
+    // precedence.
+    fn has_well_known_mapper(
+        &self,
+        direction: &Direction<FromProstFieldInfo, IntoProstFieldInfo>,
+    ) -> bool {
+        if direction.has_user_mapper() {
+            return false;
+        }
+        *self.well_known || is_well_known_type(self.leaf_type()) || self.is_bytes()
+    }
+
+    // The innermost type of the field, below all of its containers.
+    fn leaf_type(&self) -> &syn::Type {
+        self.containers().last().map_or(&self.ty, |c| c.inner())
+    }
+
+    fn is_bytes(&self) -> bool {
+        !*self.well_known && (*self.bytes || is_bytes_type(self.leaf_type()))
+    }
+
+    // Whether converting from prost fails on a length mismatch, for arrays and
+    // bytes types other than `Vec<u8>`, `Box<[u8]>` and `bytes::Bytes`.
+    fn has_fixed_length(
+        &self,
+        direction: &Direction<FromProstFieldInfo, IntoProstFieldInfo>,
+    ) -> bool {
+        self.containers().any(|c| matches!(c, Container::Array(_)))
+            || (self.has_well_known_mapper(direction)
+                && self.is_bytes()
+                && !is_growable_bytes(self.leaf_type()))
+    }
+
+    // Whether `ty` is converted as a whole by `prost_dto::ProstBytes`, e.g.
+    // `bytes::Bytes`, or `Vec<u8>` in a `#[prost(bytes)]` field. User mappers
+    // also convert them as a whole.
+    fn is_bytes_value(&self, ty: &syn::Type) -> bool {
+        is_bytes_type(ty) || (*self.bytes && is_byte_string(ty))
+    }
+
+    // Whether `ty` is converted as a whole rather than as a container, for
+    // bytes values and `Vec<serde_json::Value>` in `#[prost(well_known)]`
+    // fields, a `google.protobuf.ListValue`.
+    fn is_whole_value(&self, ty: &syn::Type) -> bool {
+        self.is_bytes_value(ty) || (*self.well_known && is_json_list(ty))
+    }
+
+    // In fallible mode, generates the conversion of a single (inner) value
+    // into a `Result<T, ConversionError>`. Returns None if the conversion is
+    // infallible, that is, when the value is handled by a `map` function.
+    fn gen_try_convert(
+        &self,
+        direction: &Direction<FromProstFieldInfo, IntoProstFieldInfo>,
+        input: TokenStream,
+    ) -> Option<TokenStream> {
+        if !direction.is_fallible() {
+            return None;
+        }
+        let span = self.span();
+        match self.wrap_with_mapper(direction.clone(), input.clone()) {
+            Some(mapped) if self.has_well_known_mapper(direction) => Some(mapped),
+            Some(mapped) if self.has_try_mapper(direction) => Some(quote_spanned! { span =>
+                #mapped.map_err(::prost_dto::ConversionError::mapper)
+            }),
+            Some(_) => None,
+            None => Some(quote_spanned! { span =>
+                ::std::convert::TryInto::try_into(#input).map_err(::prost_dto::ConversionError::from)
+            }),
+        }
     }
 
     // Wraps input with mapper function. E.g. `mapper(input)` or
@@ -213,11 +1328,13 @@ impl ProstFieldInfo {
         direction: Direction<FromProstFieldInfo, IntoProstFieldInfo>,
         input: TokenStream,
     ) -> Option<TokenStream> {
+        // A function path is called as is, a `map_expr` is parenthesized.
         fn gen_mapped_inner(
             by_ref: SpannedValue<bool>,
-            mapper_path: &syn::Path,
+            mapper_path: Option<&syn::Path>,
+            mapper_expr: Option<&syn::Expr>,
             input: TokenStream,
-        ) -> TokenStream {
+        ) -> Option<TokenStream> {
             // do we have a built-in mapper?
             let span = by_ref.span();
             let opt_ref = if *by_ref {
@@ -225,28 +1342,168 @@ impl ProstFieldInfo {
             } else {
                 quote! {}
             };
-            let span = mapper_path.span();
-            quote_spanned! { span =>
-                #mapper_path(#opt_ref #input)
+            match (mapper_path, mapper_expr) {
+                (Some(mapper_path), _) => {
+                    let span = mapper_path.span();
+                    Some(quote_spanned! { span =>
+                        #mapper_path(#opt_ref #input)
+                    })
+                }
+                (None, Some(mapper_expr)) => {
+                    let span = mapper_expr.span();
+                    Some(quote_spanned! { span =>
+                        (#mapper_expr)(#opt_ref #input)
+                    })
+                }
+                (None, None) => None,
             }
         }
 
         match direction {
-            Direction::IntoProst(info) if info.map.is_some() => Some(gen_mapped_inner(
+            Direction::IntoProst(info) if direction.has_user_mapper() => gen_mapped_inner(
                 info.map_by_ref,
-                info.map.as_ref().unwrap(),
+                info.map.as_ref(),
+                info.map_expr.as_ref(),
                 input,
-            )),
-            Direction::FromProst(info) if info.map.is_some() => Some(gen_mapped_inner(
+            ),
+            Direction::FromProst(info) if direction.has_user_mapper() => gen_mapped_inner(
                 info.map_by_ref,
-                info.map.as_ref().unwrap(),
+                info.map.as_ref().or(info.try_map.as_ref()),
+                info.map_expr.as_ref(),
                 input,
-            )),
+            ),
+            ref direction if self.has_well_known_mapper(direction) => {
+                let span = self.span();
+                let mapper = if self.is_bytes() {
+                    quote_spanned! { span => ::prost_dto::ProstBytes }
+                } else {
+                    quote_spanned! { span => ::prost_dto::WellKnown }
+                };
+                Some(match direction {
+                    Direction::IntoProst(_) => quote_spanned! { span =>
+                        #mapper::into_proto(#input)
+                    },
+                    Direction::FromProst(_) if direction.is_fallible() => quote_spanned! { span =>
+                        #mapper::try_from_proto(#input)
+                    },
+                    // Only infallible bytes types (e.g. `Vec<u8>`) are left,
+                    // the others need TryFromProst.
+                    Direction::FromProst(_) => quote_spanned! { span =>
+                        #mapper::try_from_proto(#input).unwrap()
+                    },
+                })
+            }
             _ => None,
         }
     }
 }
 
+// A container layer of a field type.
+enum Container<'a> {
+    Option(&'a syn::Type),
+    Vec(&'a syn::Type),
+    Map {
+        value: &'a syn::Type,
+        hashmap: bool,
+    },
+    // Any other repeated or map field, collected into `ty` from prost.
+    Collection {
+        kind: CollectionKind,
+        ty: &'a syn::Type,
+        element: &'a syn::Type,
+    },
+    Pointer(SmartPointer, &'a syn::Type),
+    // A fixed-size array of a repeated field.
+    Array(&'a syn::Type),
+}
+
+impl<'a> Container<'a> {
+    fn of(ty: &'a syn::Type) -> Option<Self> {
+        if let Some(inner) = extract_inner_type_from_container(ty, option_segment) {
+            Some(Container::Option(inner))
+        } else if let Some(inner) = extract_inner_type_from_container(ty, vec_segment) {
+            Some(Container::Vec(inner))
+        } else if let Some(value) = extract_map_value_type(ty) {
+            Some(Container::Map {
+                value,
+                hashmap: extract_inner_type_from_container(ty, hashmap_segment).is_some(),
+            })
+        } else if let Some((pointer, inner)) = extract_smart_pointer(ty) {
+            Some(Container::Pointer(pointer, inner))
+        } else {
+            Container::collection(ty, false)
+        }
+    }
+
+    fn collection(ty: &'a syn::Type, custom: bool) -> Option<Self> {
+        extract_collection(ty, custom).map(|(kind, element)| Container::Collection {
+            kind,
+            ty,
+            element,
+        })
+    }
+
+    fn inner(&self) -> &'a syn::Type {
+        match *self {
+            Container::Option(inner)
+            | Container::Vec(inner)
+            | Container::Pointer(_, inner)
+            | Container::Array(inner) => inner,
+            Container::Map { value, .. } => value,
+            Container::Collection { element, .. } => element,
+        }
+    }
+
+    fn is_collection(&self) -> bool {
+        matches!(
+            self,
+            Container::Vec(_)
+                | Container::Map { .. }
+                | Container::Collection { .. }
+                | Container::Array(_)
+        )
+    }
+
+    fn is_set(&self) -> bool {
+        matches!(
+            self,
+            Container::Collection {
+                kind: CollectionKind::Set,
+                ..
+            }
+        )
+    }
+
+    fn is_pointer(&self) -> bool {
+        matches!(self, Container::Pointer(..))
+    }
+
+    // The type collected into, prost repeated fields are `Vec`s and its maps
+    // are inferred.
+    fn collect_type(&self, into: bool, span: proc_macro2::Span) -> TokenStream {
+        match *self {
+            Container::Vec(_) | Container::Array(_) => {
+                quote_spanned! { span => ::std::vec::Vec<_> }
+            }
+            Container::Map { hashmap: true, .. } => {
+                quote_spanned! { span => ::std::collections::HashMap<_, _> }
+            }
+            Container::Map { hashmap: false, .. } => {
+                quote_spanned! { span => ::std::collections::BTreeMap<_, _> }
+            }
+            Container::Collection { ty, .. } if !into => ty.to_token_stream(),
+            Container::Collection {
+                kind: CollectionKind::Map,
+                ..
+            } => quote_spanned! { span => _ },
+            Container::Collection { .. } => quote_spanned! { span => ::std::vec::Vec<_> },
+            Container::Option(_) | Container::Pointer(..) => {
+                unreachable!("only collections are collected")
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use darling::FromField;
@@ -292,6 +1549,19 @@ mod tests {
         gen_tokens_test_helper(info, direction, expected)
     }
 
+    #[track_caller]
+    fn gen_tokens_test_helper_try_from(
+        field: &syn::Field,
+        info: ProstFieldInfo,
+        expected: TokenStream,
+    ) -> darling::Result<()> {
+        let mut from_field_info = FromProstFieldInfo::from_field(field)?;
+        from_field_info.fallible = true;
+        let direction: Direction<FromProstFieldInfo, IntoProstFieldInfo> =
+            Direction::FromProst(from_field_info);
+        gen_tokens_test_helper(info, direction, expected)
+    }
+
     fn field_from_quote(quote: TokenStream) -> darling::Result<(syn::Field, ProstFieldInfo)> {
         let field: syn::Field = syn::Field::parse_named.parse2(quote).unwrap();
         Ok((field.clone(), ProstFieldInfo::from_field(&field)?))
@@ -684,4 +1954,1017 @@ mod tests {
         }
         Ok(())
     }
+
+    #[test]
+    fn gen_tokens_oneof() -> darling::Result<()> {
+        // Bare type, the oneof must be set.
+        {
+            let (field, field_info) = field_from_quote(quote! {
+                #[prost(oneof = "payload")]
+                pub body: Body
+            })?;
+
+            gen_tokens_test_helper_into(
+                &field,
+                field_info.clone(),
+                quote! { payload: Some(value.body.into()), },
+            )?;
+            gen_tokens_test_helper_from(
+                &field,
+                field_info.clone(),
+                quote! { body: value.payload.unwrap().into(), },
+            )?;
+            gen_tokens_test_helper_try_from(
+                &field,
+                field_info,
+                quote! {
+                    body: ::std::convert::TryInto::try_into(value.payload.ok_or_else(||
+                        ::prost_dto::ConversionError::missing_oneof("payload"))?)
+                        .map_err(::prost_dto::ConversionError::from)
+                        .map_err(|e| e.at_field("payload"))?,
+                },
+            )?;
+        }
+
+        // Optional oneof.
+        {
+            let (field, field_info) = field_from_quote(quote! {
+                #[prost(oneof)]
+                pub payload: Option<Payload>
+            })?;
+
+            gen_tokens_test_helper_into(
+                &field,
+                field_info.clone(),
+                quote! { payload: value.payload.map(Into::into), },
+            )?;
+            gen_tokens_test_helper_from(
+                &field,
+                field_info,
+                quote! { payload: value.payload.map(Into::into), },
+            )?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn gen_tokens_enumeration() -> darling::Result<()> {
+        let (field, field_info) = field_from_quote(quote! {
+            #[prost(enumeration = "proto::HttpMethod")]
+            pub method: HttpMethod
+        })?;
+
+        gen_tokens_test_helper_into(
+            &field,
+            field_info.clone(),
+            quote! { method: ::std::convert::Into::<i32>::into(value.method), },
+        )?;
+        // Unknown values can't be converted by FromProst.
+        let err = gen_tokens_test_helper_from(&field, field_info.clone(), quote! {}).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "`enumeration` fields are only supported by TryFromProst"
+        );
+        gen_tokens_test_helper_try_from(
+            &field,
+            field_info,
+            quote! {
+                method: <proto::HttpMethod as ::std::convert::TryFrom<i32>>::try_from(value.method)
+                    .map_err(|_| ::prost_dto::ConversionError::unknown_enum_value(
+                        "proto::HttpMethod", value.method))
+                    .and_then(|e| ::std::convert::TryInto::try_into(e)
+                        .map_err(::prost_dto::ConversionError::from))
+                    .map_err(|e| e.at_field("method"))?,
+            },
+        )?;
+
+        // The sentinel of optional fields is `None`, unknown values are still
+        // rejected.
+        let (field, field_info) = field_from_quote(quote! {
+            #[prost(enumeration = "proto::HttpMethod")]
+            pub fallback: Option<HttpMethod>
+        })?;
+        let err = gen_tokens_test_helper_from(&field, field_info.clone(), quote! {}).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "`enumeration` fields are only supported by TryFromProst"
+        );
+        gen_tokens_test_helper_try_from(
+            &field,
+            field_info,
+            quote! {
+                fallback: ::prost_dto::EnumerationRepr::into_option(value.fallback)
+                    .map(<HttpMethod as ::prost_dto::ProstEnumeration>::try_from_i32_or_none)
+                    .transpose()
+                    .map(::std::option::Option::flatten)
+                    .map_err(|e| e.at_field("fallback"))?,
+            },
+        )?;
+        Ok(())
+    }
+
+    #[test]
+    fn gen_tokens_well_known_fallback() -> darling::Result<()> {
+        // Bare names aren't detected, they're converted with `Into`.
+        let (field, field_info) = field_from_quote(quote! {
+            pub created_at: Option<SystemTime>
+        })?;
+        gen_tokens_test_helper_into(
+            &field,
+            field_info,
+            quote! { created_at: value.created_at.map(Into::into), },
+        )?;
+        Ok(())
+    }
+
+    #[cfg(feature = "serde_json")]
+    #[test]
+    fn gen_tokens_json_list() -> darling::Result<()> {
+        // A repeated `google.protobuf.Value` by default.
+        let (field, field_info) = field_from_quote(quote! {
+            pub tags: Vec<serde_json::Value>
+        })?;
+        gen_tokens_test_helper_into(
+            &field,
+            field_info,
+            quote! {
+                tags: value.tags.into_iter()
+                    .map(|v| ::prost_dto::WellKnown::into_proto(v))
+                    .collect::<::std::vec::Vec<_>>(),
+            },
+        )?;
+
+        // And a `google.protobuf.ListValue` when marked.
+        let (field, field_info) = field_from_quote(quote! {
+            #[prost(well_known)]
+            pub tags: Option<Vec<serde_json::Value>>
+        })?;
+        gen_tokens_test_helper_into(
+            &field,
+            field_info,
+            quote! {
+                tags: value.tags.map(|v| ::prost_dto::WellKnown::into_proto(v)),
+            },
+        )?;
+        Ok(())
+    }
+
+    #[cfg(feature = "prost-types")]
+    #[test]
+    fn gen_tokens_well_known() -> darling::Result<()> {
+        // Detected by type
+        {
+            let (field, field_info) = field_from_quote(quote! {
+                pub created_at: Option<std::time::SystemTime>
+            })?;
+
+            gen_tokens_test_helper_into(
+                &field,
+                field_info.clone(),
+                quote! {
+                    created_at: value.created_at.map(|v| ::prost_dto::WellKnown::into_proto(v)),
+                },
+            )?;
+            let err =
+                gen_tokens_test_helper_from(&field, field_info.clone(), quote! {}).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "well-known types are only supported by TryFromProst"
+            );
+            gen_tokens_test_helper_try_from(
+                &field,
+                field_info,
+                quote! {
+                    created_at: value.created_at
+                        .map(|v| ::prost_dto::WellKnown::try_from_proto(v))
+                        .transpose()
+                        .map_err(|e| e.at_field("created_at"))?,
+                },
+            )?;
+        }
+
+        // Explicit, required
+        {
+            let (field, field_info) = field_from_quote(quote! {
+                #[prost(well_known, required)]
+                pub timeout: Duration
+            })?;
+
+            gen_tokens_test_helper_into(
+                &field,
+                field_info.clone(),
+                quote! {
+                    timeout: Some(::prost_dto::WellKnown::into_proto(value.timeout)),
+                },
+            )?;
+            gen_tokens_test_helper_try_from(
+                &field,
+                field_info,
+                quote! {
+                    timeout: ::prost_dto::WellKnown::try_from_proto(value.timeout.ok_or_else(||
+                        ::prost_dto::ConversionError::missing_required("timeout"))?)
+                        .map_err(|e| e.at_field("timeout"))?,
+                },
+            )?;
+        }
+
+        // User mappers take precedence
+        {
+            let (field, field_info) = field_from_quote(quote! {
+                #[into_prost(map = "to_proto")]
+                pub created_at: std::time::SystemTime
+            })?;
+
+            gen_tokens_test_helper_into(
+                &field,
+                field_info,
+                quote! { created_at: to_proto(value.created_at).into(), },
+            )?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn gen_tokens_with() -> darling::Result<()> {
+        let (field, field_info) = field_from_quote(quote! {
+            #[prost(with = "cents")]
+            pub balance: Option<f64>
+        })?;
+        gen_tokens_test_helper_into(
+            &field,
+            field_info.clone(),
+            quote! { balance: value.balance.map(|v| cents::into_prost(v)), },
+        )?;
+        gen_tokens_test_helper_from(
+            &field,
+            field_info,
+            quote! { balance: value.balance.map(|v| cents::from_prost(v)), },
+        )?;
+
+        let (field, field_info) = field_from_quote(quote! {
+            #[prost(with = "cents")]
+            #[into_prost(map = "to_cents")]
+            pub balance: f64
+        })?;
+        let err = field_info
+            .gen_tokens(Direction::IntoProst(IntoProstFieldInfo::from_field(
+                &field,
+            )?))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "`with` can't be combined with `map`, `map_expr` or `try_map`"
+        );
+
+        // `try_with` modules can fail in TryFromProst.
+        let (field, field_info) = field_from_quote(quote! {
+            #[prost(try_with = "cents")]
+            pub balance: f64
+        })?;
+        gen_tokens_test_helper_into(
+            &field,
+            field_info.clone(),
+            quote! { balance: cents::into_prost(value.balance), },
+        )?;
+        gen_tokens_test_helper_try_from(
+            &field,
+            field_info.clone(),
+            quote! {
+                balance: cents::from_prost(value.balance)
+                    .map_err(::prost_dto::ConversionError::mapper)
+                    .map_err(|e| e.at_field("balance"))?,
+            },
+        )?;
+        let err = gen_tokens_test_helper_from(&field, field_info, quote! {}).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "`try_with` is only supported by TryFromProst"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn gen_tokens_map_expr() -> darling::Result<()> {
+        let (field, field_info) = field_from_quote(quote! {
+            #[into_prost(map_expr = "|v: u32| v * 2")]
+            #[from_prost(map_expr = "|v: u32| v / 2")]
+            pub count: Vec<u32>
+        })?;
+        gen_tokens_test_helper_into(
+            &field,
+            field_info.clone(),
+            quote! {
+                count: value.count.into_iter()
+                    .map(|v| (|v: u32| v * 2)(v))
+                    .collect::<::std::vec::Vec<_>>(),
+            },
+        )?;
+        gen_tokens_test_helper_from(
+            &field,
+            field_info,
+            quote! {
+                count: value.count.into_iter()
+                    .map(|v| (|v: u32| v / 2)(v))
+                    .collect::<::std::vec::Vec<_>>(),
+            },
+        )?;
+
+        // `with` modules produce the destination type.
+        let (field, field_info) = field_from_quote(quote! {
+            #[prost(with = "text")]
+            pub label: u32
+        })?;
+        gen_tokens_test_helper_from(
+            &field,
+            field_info,
+            quote! { label: text::from_prost(value.label), },
+        )?;
+        Ok(())
+    }
+
+    #[test]
+    fn gen_tokens_nested_containers() -> darling::Result<()> {
+        let (field, field_info) = field_from_quote(quote! {
+            #[into_prost(map = "to_proto")]
+            pub children: Option<Vec<Child>>
+        })?;
+        gen_tokens_test_helper_into(
+            &field,
+            field_info.clone(),
+            quote! {
+                children: value.children
+                    .map(|v| v.into_iter().map(|v| to_proto(v)).collect::<::std::vec::Vec<_> >())
+                    .unwrap_or_default(),
+            },
+        )?;
+        gen_tokens_test_helper_from(
+            &field,
+            field_info,
+            quote! {
+                children: ::std::option::Option::Some(value.children)
+                    .filter(|v| !v.is_empty())
+                    .map(|v| v.into_iter()
+                        .map(|v| ::std::convert::Into::into(v))
+                        .collect::<::std::vec::Vec<_> >()),
+            },
+        )?;
+
+        let (field, field_info) = field_from_quote(quote! {
+            pub grid: Vec<Vec<u32>>
+        })?;
+        gen_tokens_test_helper_try_from(
+            &field,
+            field_info,
+            quote! {
+                grid: value.grid.into_iter().enumerate()
+                    .map(|(i, v)| v.into_iter().enumerate()
+                        .map(|(i, v)| ::std::convert::TryInto::try_into(v)
+                            .map_err(::prost_dto::ConversionError::from)
+                            .map_err(|e| e.at_index(i)))
+                        .collect::<::std::result::Result<::std::vec::Vec<_>, ::prost_dto::ConversionError>>()
+                        .map_err(|e| e.at_index(i)))
+                    .collect::<::std::result::Result<::std::vec::Vec<_>, ::prost_dto::ConversionError>>()
+                    .map_err(|e| e.at_field("grid"))?,
+            },
+        )?;
+        Ok(())
+    }
+
+    #[test]
+    fn gen_tokens_collections() -> darling::Result<()> {
+        // Collected into our type from prost, and into a `Vec` into prost.
+        let (field, field_info) = field_from_quote(quote! {
+            pub tags: BTreeSet<Tag>
+        })?;
+        gen_tokens_test_helper_into(
+            &field,
+            field_info.clone(),
+            quote! {
+                tags: value.tags.into_iter().map(|v| ::std::convert::Into::into(v))
+                    .collect::<::std::vec::Vec<_> >(),
+            },
+        )?;
+        gen_tokens_test_helper_from(
+            &field,
+            field_info,
+            quote! {
+                tags: value.tags.into_iter().map(|v| ::std::convert::Into::into(v))
+                    .collect::<BTreeSet<Tag> >(),
+            },
+        )?;
+
+        let (field, field_info) = field_from_quote(quote! {
+            #[prost(collection)]
+            pub stack: Stack<u32>
+        })?;
+        gen_tokens_test_helper_from(
+            &field,
+            field_info,
+            quote! {
+                stack: value.stack.into_iter().map(|v| ::std::convert::Into::into(v))
+                    .collect::<Stack<u32> >(),
+            },
+        )?;
+
+        let (field, field_info) = field_from_quote(quote! {
+            #[from_prost(deny_duplicates)]
+            pub ids: HashSet<u32>
+        })?;
+        gen_tokens_test_helper_try_from(
+            &field,
+            field_info,
+            quote! {
+                ids: value.ids.into_iter().enumerate().try_fold(
+                    <HashSet<u32> as ::std::default::Default>::default(),
+                    |mut set, (i, v)| {
+                        let v = ::std::convert::TryInto::try_into(v)
+                            .map_err(::prost_dto::ConversionError::from)
+                            .map_err(|e| e.at_index(i))?;
+                        let len = set.len();
+                        ::std::iter::Extend::extend(&mut set, ::std::iter::once(v));
+                        if set.len() == len {
+                            ::std::result::Result::Err(
+                                ::prost_dto::ConversionError::duplicate_value().at_index(i),
+                            )
+                        } else {
+                            ::std::result::Result::Ok(set)
+                        }
+                    },
+                )
+                .map_err(|e| e.at_field("ids"))?,
+            },
+        )?;
+        Ok(())
+    }
+
+    #[test]
+    fn gen_tokens_map_keys() -> darling::Result<()> {
+        let (field, field_info) = field_from_quote(quote! {
+            #[into_prost(map_key = "UserId::into_string")]
+            #[from_prost(try_map_key = "UserId::parse")]
+            pub users: HashMap<UserId, User>
+        })?;
+        gen_tokens_test_helper_into(
+            &field,
+            field_info.clone(),
+            quote! {
+                users: value.users.into_iter()
+                    .map(|(k, v)| (UserId::into_string(k), v.into()))
+                    .collect::<::std::collections::HashMap<_, _> >(),
+            },
+        )?;
+        gen_tokens_test_helper_try_from(
+            &field,
+            field_info,
+            quote! {
+                users: value.users.into_iter()
+                    .map(|(k, v)| match ::std::convert::TryInto::try_into(v)
+                        .map_err(::prost_dto::ConversionError::from)
+                    {
+                        ::std::result::Result::Ok(v) => UserId::parse(::std::clone::Clone::clone(&k))
+                            .map(|key| (key, v))
+                            .map_err(|e| ::prost_dto::ConversionError::mapper(e).at_key(&k)),
+                        ::std::result::Result::Err(e) => ::std::result::Result::Err(e.at_key(&k)),
+                    })
+                    .collect::<::std::result::Result<::std::collections::HashMap<_, _>, ::prost_dto::ConversionError>>()
+                    .map_err(|e| e.at_field("users"))?,
+            },
+        )?;
+
+        // Entries are tuples or structs with `key` and `value` fields.
+        let (field, field_info) = field_from_quote(quote! {
+            #[prost(map_entries)]
+            pub labels: Vec<(String, String)>
+        })?;
+        gen_tokens_test_helper_from(
+            &field,
+            field_info,
+            quote! {
+                labels: value.labels.into_iter()
+                    .map(|(k, v)| (k.into(), ::std::convert::Into::into(v)))
+                    .collect::<::std::vec::Vec<_>>(),
+            },
+        )?;
+        let (field, field_info) = field_from_quote(quote! {
+            #[prost(map_entries)]
+            pub members: Vec<Entry<User>>
+        })?;
+        gen_tokens_test_helper_into(
+            &field,
+            field_info,
+            quote! {
+                members: value.members.into_iter()
+                    .map(|Entry::<User> { key: k, value: v, .. }| (k.into(), ::std::convert::Into::into(v)))
+                    .collect(),
+            },
+        )?;
+        Ok(())
+    }
+
+    #[test]
+    fn gen_tokens_bytes_and_arrays() -> darling::Result<()> {
+        // Byte strings are converted byte by byte by default.
+        let (field, field_info) = field_from_quote(quote! {
+            pub payload: Vec<u8>
+        })?;
+        gen_tokens_test_helper_into(
+            &field,
+            field_info,
+            quote! {
+                payload: value.payload.into_iter().map(Into::into).collect::<::std::vec::Vec<_>>(),
+            },
+        )?;
+
+        // And as a whole when marked.
+        let (field, field_info) = field_from_quote(quote! {
+            #[prost(bytes)]
+            pub payload: Vec<u8>
+        })?;
+        gen_tokens_test_helper_into(
+            &field,
+            field_info.clone(),
+            quote! {
+                payload: ::prost_dto::ProstBytes::into_proto(value.payload),
+            },
+        )?;
+        gen_tokens_test_helper_from(
+            &field,
+            field_info,
+            quote! {
+                payload: ::prost_dto::ProstBytes::try_from_proto(value.payload).unwrap(),
+            },
+        )?;
+        let (field, field_info) = field_from_quote(quote! {
+            #[prost(bytes)]
+            pub hash: [u8; 32]
+        })?;
+        gen_tokens_test_helper_try_from(
+            &field,
+            field_info.clone(),
+            quote! {
+                hash: ::prost_dto::ProstBytes::try_from_proto(value.hash)
+                    .map_err(|e| e.at_field("hash"))?,
+            },
+        )?;
+        // Fixed-length values need TryFromProst.
+        let err = gen_tokens_test_helper_from(&field, field_info, quote! {}).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "fixed-length types are only supported by TryFromProst"
+        );
+
+        // Only full paths are detected, e.g. not a user's own `Uuid`.
+        let (field, field_info) = field_from_quote(quote! {
+            pub id: Uuid
+        })?;
+        gen_tokens_test_helper_into(&field, field_info, quote! { id: value.id.into(), })?;
+
+        // User mappers get the whole value of a `bytes` field.
+        let (field, field_info) = field_from_quote(quote! {
+            #[prost(bytes)]
+            #[into_prost(map = "compress")]
+            pub payload: Vec<u8>
+        })?;
+        gen_tokens_test_helper_into(
+            &field,
+            field_info,
+            quote! {
+                payload: compress(value.payload).into(),
+            },
+        )?;
+
+        // And a single byte otherwise.
+        let (field, field_info) = field_from_quote(quote! {
+            #[into_prost(map = "widen")]
+            pub levels: Vec<u8>
+        })?;
+        gen_tokens_test_helper_into(
+            &field,
+            field_info,
+            quote! {
+                levels: value.levels.into_iter().map(|v| widen(v)).collect::<::std::vec::Vec<_>>(),
+            },
+        )?;
+
+        // Other arrays are converted element by element, and length-checked.
+        let (field, field_info) = field_from_quote(quote! {
+            pub coords: [Coord; 3]
+        })?;
+        gen_tokens_test_helper_into(
+            &field,
+            field_info.clone(),
+            quote! {
+                coords: ::std::iter::IntoIterator::into_iter(value.coords)
+                    .map(|v| ::std::convert::Into::into(v))
+                    .collect::<::std::vec::Vec<_>>(),
+            },
+        )?;
+        let err = gen_tokens_test_helper_from(&field, field_info.clone(), quote! {}).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "fixed-length types are only supported by TryFromProst"
+        );
+        gen_tokens_test_helper_try_from(
+            &field,
+            field_info,
+            quote! {
+                coords: value.coords.into_iter().enumerate()
+                    .map(|(i, v)| ::std::convert::TryInto::try_into(v)
+                        .map_err(::prost_dto::ConversionError::from)
+                        .map_err(|e| e.at_index(i)))
+                    .collect::<::std::result::Result<::std::vec::Vec<_>, ::prost_dto::ConversionError>>()
+                    .and_then(::prost_dto::array_from_vec)
+                    .map_err(|e| e.at_field("coords"))?,
+            },
+        )?;
+        Ok(())
+    }
+
+    #[test]
+    fn gen_tokens_smart_pointers() -> darling::Result<()> {
+        // Our pointer is unwrapped, the prost field is boxed.
+        let (field, field_info) = field_from_quote(quote! {
+            #[prost(boxed)]
+            pub parent: Option<Arc<Node>>
+        })?;
+        gen_tokens_test_helper_into(
+            &field,
+            field_info.clone(),
+            quote! {
+                parent: value.parent.map(|v| ::std::boxed::Box::new(
+                    ::std::convert::Into::into(::std::sync::Arc::unwrap_or_clone(v)))),
+            },
+        )?;
+        gen_tokens_test_helper_from(
+            &field,
+            field_info,
+            quote! {
+                parent: value.parent.map(|v| ::std::sync::Arc::new(::std::convert::Into::into(*v))),
+            },
+        )?;
+
+        let (field, field_info) = field_from_quote(quote! {
+            #[prost(required)]
+            pub config: Box<Config>
+        })?;
+        gen_tokens_test_helper_into(
+            &field,
+            field_info.clone(),
+            quote! {
+                config: Some(::std::convert::Into::into(*value.config)),
+            },
+        )?;
+        gen_tokens_test_helper_try_from(
+            &field,
+            field_info,
+            quote! {
+                config: ::std::convert::TryInto::try_into(value.config.ok_or_else(||
+                        ::prost_dto::ConversionError::missing_required("config"))?)
+                    .map_err(::prost_dto::ConversionError::from)
+                    .map(::std::boxed::Box::new)
+                    .map_err(|e| e.at_field("config"))?,
+            },
+        )?;
+        Ok(())
+    }
+
+    #[test]
+    fn gen_tokens_by_ref() -> darling::Result<()> {
+        let into_ref = |field: &syn::Field| -> darling::Result<_> {
+            let mut direction: Direction<FromProstFieldInfo, IntoProstFieldInfo> =
+                Direction::IntoProst(IntoProstFieldInfo::from_field(field)?);
+            direction.set_by_ref();
+            Ok(direction)
+        };
+
+        // Leaves are cloned, collections are iterated by reference.
+        let (field, field_info) = field_from_quote(quote! {
+            pub tags: Vec<String>
+        })?;
+        gen_tokens_test_helper(
+            field_info,
+            into_ref(&field)?,
+            quote! {
+                tags: value.tags.iter()
+                    .map(|v| ::std::convert::Into::into(::std::clone::Clone::clone(v)))
+                    .collect::<::std::vec::Vec<_>>(),
+            },
+        )?;
+
+        // Nested DTOs convert from a reference.
+        let (field, field_info) = field_from_quote(quote! {
+            #[prost(by_ref)]
+            pub origin: Option<Point>
+        })?;
+        gen_tokens_test_helper(
+            field_info,
+            into_ref(&field)?,
+            quote! {
+                origin: value.origin.as_ref().map(|v| ::std::convert::Into::into(v)),
+            },
+        )?;
+
+        // So are nested containers.
+        let (field, field_info) = field_from_quote(quote! {
+            pub groups: Option<Vec<String>>
+        })?;
+        gen_tokens_test_helper(
+            field_info,
+            into_ref(&field)?,
+            quote! {
+                groups: ::std::option::Option::as_ref(&value.groups)
+                    .map(|v| ::std::iter::IntoIterator::into_iter(v)
+                        .map(|v| ::std::convert::Into::into(::std::clone::Clone::clone(v)))
+                        .collect::<::std::vec::Vec<_> >())
+                    .unwrap_or_default(),
+            },
+        )?;
+
+        // `map_by_ref` mappers borrow the value.
+        let (field, field_info) = field_from_quote(quote! {
+            #[into_prost(map = "to_proto", map_by_ref)]
+            pub name: Name
+        })?;
+        gen_tokens_test_helper(
+            field_info,
+            into_ref(&field)?,
+            quote! {
+                name: ::std::convert::Into::into(to_proto(&(*&value.name))),
+            },
+        )?;
+        Ok(())
+    }
+
+    #[test]
+    fn gen_tokens_wrapper() -> darling::Result<()> {
+        let (field, field_info) = field_from_quote(quote! {
+            #[prost(wrapper = "proto::StringValue")]
+            pub nickname: Option<String>
+        })?;
+
+        gen_tokens_test_helper_into(
+            &field,
+            field_info.clone(),
+            quote! {
+                nickname: value.nickname.map(|v| proto::StringValue { value: v.into() }),
+            },
+        )?;
+        gen_tokens_test_helper_from(
+            &field,
+            field_info.clone(),
+            quote! { nickname: value.nickname.map(|v| v.value.into()), },
+        )?;
+        gen_tokens_test_helper_try_from(
+            &field,
+            field_info,
+            quote! {
+                nickname: value.nickname
+                    .map(|v| ::std::convert::TryInto::try_into(v.value)
+                        .map_err(::prost_dto::ConversionError::from))
+                    .transpose()
+                    .map_err(|e| e.at_field("nickname"))?,
+            },
+        )?;
+
+        // The wrapper message can be omitted in FromProst.
+        let (field, field_info) = field_from_quote(quote! {
+            #[prost(wrapper)]
+            #[from_prost(map = "to_age")]
+            pub age: Option<u32>
+        })?;
+        gen_tokens_test_helper_from(
+            &field,
+            field_info.clone(),
+            quote! { age: value.age.map(|v| to_age(v.value)), },
+        )?;
+        let mut acc = Accumulator::default();
+        acc.handle(
+            field_info.gen_tokens(Direction::IntoProst(IntoProstFieldInfo::from_field(
+                &field,
+            )?)),
+        );
+        assert!(acc.finish().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn gen_tokens_fallible() -> darling::Result<()> {
+        // Bare type
+        {
+            let (field, field_info) = field_from_quote(quote! { pub foo: u32 })?;
+
+            gen_tokens_test_helper_try_from(
+                &field,
+                field_info,
+                quote! {
+                    foo: ::std::convert::TryInto::try_into(value.foo)
+                        .map_err(::prost_dto::ConversionError::from)
+                        .map_err(|e| e.at_field("foo"))?,
+                },
+            )?;
+        }
+
+        // #[prost(required)] bare type reports missing values.
+        {
+            let (field, field_info) = field_from_quote(quote! {
+                #[prost(required)]
+                #[prost(name = "bar")]
+                pub foo: u32
+            })?;
+
+            gen_tokens_test_helper_try_from(
+                &field,
+                field_info,
+                quote! {
+                    foo: ::std::convert::TryInto::try_into(value.bar.ok_or_else(||
+                        ::prost_dto::ConversionError::missing_required("bar"))?)
+                        .map_err(::prost_dto::ConversionError::from)
+                        .map_err(|e| e.at_field("bar"))?,
+                },
+            )?;
+        }
+
+        // Mapped bare type, the mapper output is converted with try_into.
+        {
+            let (field, field_info) = field_from_quote(quote! {
+                #[from_prost(map = "String::from")]
+                pub foo: String
+            })?;
+
+            gen_tokens_test_helper_try_from(
+                &field,
+                field_info,
+                quote! {
+                    foo: ::std::convert::TryInto::try_into(String::from(value.foo))
+                        .map_err(::prost_dto::ConversionError::from)
+                        .map_err(|e| e.at_field("foo"))?,
+                },
+            )?;
+        }
+
+        // Fallible mapper on a bare type.
+        {
+            let (field, field_info) = field_from_quote(quote! {
+                #[from_prost(try_map = "Url::parse", map_by_ref)]
+                pub foo: Url
+            })?;
+
+            gen_tokens_test_helper_try_from(
+                &field,
+                field_info,
+                quote! {
+                    foo: Url::parse(&value.foo)
+                        .map_err(::prost_dto::ConversionError::mapper)
+                        .and_then(|v| ::std::convert::TryInto::try_into(v)
+                            .map_err(::prost_dto::ConversionError::from))
+                        .map_err(|e| e.at_field("foo"))?,
+                },
+            )?;
+        }
+
+        // Option<T>
+        {
+            let (field, field_info) = field_from_quote(quote! { pub foo: Option<u32> })?;
+
+            gen_tokens_test_helper_try_from(
+                &field,
+                field_info,
+                quote! {
+                    foo: value.foo.map(|v| ::std::convert::TryInto::try_into(v)
+                        .map_err(::prost_dto::ConversionError::from))
+                        .transpose()
+                        .map_err(|e| e.at_field("foo"))?,
+                },
+            )?;
+        }
+
+        // Option<T> with an infallible mapper, nothing to propagate.
+        {
+            let (field, field_info) = field_from_quote(quote! {
+                #[from_prost(map = "String::from")]
+                pub foo: Option<u32>
+            })?;
+
+            gen_tokens_test_helper_try_from(
+                &field,
+                field_info,
+                quote! { foo: value.foo.map(|v| String::from(v)), },
+            )?;
+        }
+
+        // Vec<T>, errors record the index.
+        {
+            let (field, field_info) = field_from_quote(quote! { pub foo: Vec<u32> })?;
+
+            gen_tokens_test_helper_try_from(
+                &field,
+                field_info,
+                quote! {
+                    foo: value.foo.into_iter().enumerate()
+                        .map(|(i, v)| ::std::convert::TryInto::try_into(v)
+                            .map_err(::prost_dto::ConversionError::from)
+                            .map_err(|e| e.at_index(i)))
+                        .collect::<::std::result::Result<::std::vec::Vec<_>, ::prost_dto::ConversionError>>()
+                        .map_err(|e| e.at_field("foo"))?,
+                },
+            )?;
+        }
+
+        // HashMap<K, V>, errors record the key.
+        {
+            let (field, field_info) = field_from_quote(quote! { pub foo: HashMap<String, u32> })?;
+
+            gen_tokens_test_helper_try_from(
+                &field,
+                field_info,
+                quote! {
+                    foo: value.foo.into_iter()
+                        .map(|(k, v)| match ::std::convert::TryInto::try_into(v)
+                            .map_err(::prost_dto::ConversionError::from) {
+                            ::std::result::Result::Ok(v) => ::std::result::Result::Ok((k.into(), v)),
+                            ::std::result::Result::Err(e) => ::std::result::Result::Err(e.at_key(&k)),
+                        })
+                        .collect::<::std::result::Result<::std::collections::HashMap<_, _>, ::prost_dto::ConversionError>>()
+                        .map_err(|e| e.at_field("foo"))?,
+                },
+            )?;
+        }
+
+        // try_map requires TryFromProst
+        {
+            let (field, field_info) = field_from_quote(quote! {
+                #[from_prost(try_map = "Url::parse")]
+                pub foo: Url
+            })?;
+            let err = gen_tokens_test_helper_from(&field, field_info, quote! {}).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "`try_map` is only supported by TryFromProst"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn gen_tokens_tuple() -> darling::Result<()> {
+        let field: syn::Field = syn::Field::parse_unnamed
+            .parse2(quote! { #[prost(name = "value")] pub String })
+            .unwrap();
+        let mut field_info = ProstFieldInfo::from_field(&field)?;
+        field_info.index = 1;
+
+        gen_tokens_test_helper_into(
+            &field,
+            field_info.clone(),
+            quote! { value: value.1.into(), },
+        )?;
+        gen_tokens_test_helper_from(&field, field_info, quote! { 1: value.value.into(), })?;
+
+        // Positional fields must be named.
+        let field: syn::Field = syn::Field::parse_unnamed
+            .parse2(quote! { pub String })
+            .unwrap();
+        let field_info = ProstFieldInfo::from_field(&field)?;
+        assert!(gen_tokens_test_helper_into(&field, field_info, quote! {}).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn gen_transparent() -> darling::Result<()> {
+        let field: syn::Field = syn::Field::parse_unnamed
+            .parse2(quote! { pub Vec<String> })
+            .unwrap();
+        let field_info = ProstFieldInfo::from_field(&field)?;
+
+        let direction = Direction::IntoProst(IntoProstFieldInfo::from_field(&field)?);
+        assert_eq!(
+            field_info.clone().gen_transparent(direction)?.to_string(),
+            quote! { value.0.into_iter().map(Into::into).collect::<::std::vec::Vec<_>>() }
+                .to_string()
+        );
+
+        let direction = Direction::FromProst(FromProstFieldInfo::from_field(&field)?);
+        assert_eq!(
+            field_info.clone().gen_transparent(direction)?.to_string(),
+            quote! { 0: value.into_iter().map(Into::into).collect::<::std::vec::Vec<_>>(), }
+                .to_string()
+        );
+
+        // Errors are propagated without adding a path segment.
+        let mut from_field_info = FromProstFieldInfo::from_field(&field)?;
+        from_field_info.fallible = true;
+        let direction = Direction::FromProst(from_field_info);
+        assert_eq!(
+            field_info.gen_transparent(direction)?.to_string(),
+            quote! {
+                0: value.into_iter().enumerate()
+                    .map(|(i, v)| ::std::convert::TryInto::try_into(v)
+                        .map_err(::prost_dto::ConversionError::from)
+                        .map_err(|e| e.at_index(i)))
+                    .collect::<::std::result::Result<::std::vec::Vec<_>, ::prost_dto::ConversionError>>()?,
+            }
+            .to_string()
+        );
+        Ok(())
+    }
 }
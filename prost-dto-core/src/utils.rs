@@ -37,11 +37,227 @@ pub(crate) fn map_segment(path: &syn::Path) -> Option<&syn::PathSegment> {
     extract_generic_type_segment(path, MAP)
 }
 
+fn box_segment(path: &syn::Path) -> Option<&syn::PathSegment> {
+    static BOX: &[&str] = &["Box|", "std|boxed|Box|", "alloc|boxed|Box|"];
+    extract_generic_type_segment(path, BOX)
+}
+
 pub(crate) fn hashmap_segment(path: &syn::Path) -> Option<&syn::PathSegment> {
     static MAP: &[&str] = &["HashMap|", "std|collections|HashMap|"];
     extract_generic_type_segment(path, MAP)
 }
 
+// Repeated and map fields besides `Vec`, `HashMap` and `BTreeMap`, they are
+// converted element by element and collected into the field type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CollectionKind {
+    Seq,
+    Set,
+    Map,
+}
+
+// The kind of a collection, and its element (or value for maps) type.
+// `custom` treats any generic type as a collection, a map if it has at least
+// two type arguments. An array element (e.g. `SmallVec<[T; 4]>`) is unwrapped.
+pub(crate) fn extract_collection(
+    ty: &syn::Type,
+    custom: bool,
+) -> Option<(CollectionKind, &syn::Type)> {
+    static COLLECTIONS: &[(CollectionKind, &[&str])] = &[
+        (
+            CollectionKind::Seq,
+            &[
+                "VecDeque|",
+                "std|collections|VecDeque|",
+                "SmallVec|",
+                "smallvec|SmallVec|",
+            ],
+        ),
+        (
+            CollectionKind::Set,
+            &[
+                "HashSet|",
+                "std|collections|HashSet|",
+                "BTreeSet|",
+                "std|collections|BTreeSet|",
+                "IndexSet|",
+                "indexmap|IndexSet|",
+                "indexmap|set|IndexSet|",
+                "hashbrown|HashSet|",
+            ],
+        ),
+        (
+            CollectionKind::Map,
+            &[
+                "IndexMap|",
+                "indexmap|IndexMap|",
+                "indexmap|map|IndexMap|",
+                "hashbrown|HashMap|",
+            ],
+        ),
+    ];
+    let path = extract_type_path(ty)?;
+    let segment = path.segments.last()?;
+    let PathArguments::AngleBracketed(ref params) = segment.arguments else {
+        return None;
+    };
+    let mut type_args = params.args.iter().filter_map(|arg| match *arg {
+        GenericArgument::Type(ref ty) => Some(ty),
+        _ => None,
+    });
+    let kind = COLLECTIONS
+        .iter()
+        .find(|(_, matches)| extract_generic_type_segment(path, matches).is_some())
+        .map(|(kind, _)| *kind);
+    let kind = match kind {
+        Some(kind) => kind,
+        None if custom && type_args.clone().nth(1).is_some() => CollectionKind::Map,
+        None if custom => CollectionKind::Seq,
+        None => return None,
+    };
+    let element = match kind {
+        CollectionKind::Map => type_args.nth(1)?,
+        _ => type_args.next()?,
+    };
+    match *element {
+        syn::Type::Array(ref array) => Some((kind, &*array.elem)),
+        _ => Some((kind, element)),
+    }
+}
+
+// Smart pointers around a field value, unwrapped and rewrapped by the
+// conversion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SmartPointer {
+    Box,
+    Arc,
+    Rc,
+    Cow,
+}
+
+// The smart pointer of `ty` and its pointee, e.g. `str` for `Cow<'a, str>`.
+pub(crate) fn extract_smart_pointer(ty: &syn::Type) -> Option<(SmartPointer, &syn::Type)> {
+    static POINTERS: &[(SmartPointer, &[&str])] = &[
+        (
+            SmartPointer::Box,
+            &["Box|", "std|boxed|Box|", "alloc|boxed|Box|"],
+        ),
+        (
+            SmartPointer::Arc,
+            &["Arc|", "std|sync|Arc|", "alloc|sync|Arc|"],
+        ),
+        (SmartPointer::Rc, &["Rc|", "std|rc|Rc|", "alloc|rc|Rc|"]),
+        (
+            SmartPointer::Cow,
+            &["Cow|", "std|borrow|Cow|", "alloc|borrow|Cow|"],
+        ),
+    ];
+    let path = extract_type_path(ty)?;
+    POINTERS.iter().find_map(|(pointer, matches)| {
+        let segment = extract_generic_type_segment(path, matches)?;
+        // Skip the lifetime of `Cow`.
+        let PathArguments::AngleBracketed(ref params) = segment.arguments else {
+            return None;
+        };
+        // Unsized values, e.g. `Box<str>`, can't be moved out of the pointer.
+        params.args.iter().find_map(|arg| match *arg {
+            GenericArgument::Type(syn::Type::Slice(_)) if *pointer != SmartPointer::Cow => None,
+            GenericArgument::Type(ref ty)
+                if *pointer != SmartPointer::Cow
+                    && extract_type_path(ty).is_some_and(|path| path.is_ident("str")) =>
+            {
+                None
+            }
+            GenericArgument::Type(ref ty) => Some((*pointer, ty)),
+            _ => None,
+        })
+    })
+}
+
+// Types converted to/from protobuf well-known types by `prost_dto::WellKnown`,
+// only detected when prost-dto implements it for them. They must be written
+// as full paths, other types (e.g. a bare `SystemTime`) need
+// `#[prost(well_known)]`.
+fn well_known_segment(path: &syn::Path) -> Option<&syn::PathSegment> {
+    static PROST_TYPES: &[&str] = &[
+        "std|time|SystemTime|",
+        "std|time|Duration|",
+        "core|time|Duration|",
+    ];
+    static CHRONO: &[&str] = &["chrono|DateTime|"];
+    static TIME: &[&str] = &["time|OffsetDateTime|"];
+    static SERDE_JSON: &[&str] = &["serde_json|Value|", "serde_json|Map|"];
+    [
+        (cfg!(feature = "prost-types"), PROST_TYPES),
+        (cfg!(feature = "chrono"), CHRONO),
+        (cfg!(feature = "time"), TIME),
+        (cfg!(feature = "serde_json"), SERDE_JSON),
+    ]
+    .into_iter()
+    .filter(|(enabled, _)| *enabled)
+    .find_map(|(_, matches)| extract_generic_type_segment(path, matches))
+}
+
+// Types converted as a whole by `prost_dto::ProstBytes`, only detected when
+// prost-dto implements it for them. They must be written as full paths, byte
+// strings (e.g. `Vec<u8>`) and other types need `#[prost(bytes)]`.
+pub(crate) fn is_bytes_type(ty: &syn::Type) -> bool {
+    static BYTES: &[&str] = &["bytes|Bytes|"];
+    static UUID: &[&str] = &["uuid|Uuid|"];
+    let Some(path) = extract_type_path(ty) else {
+        return false;
+    };
+    [
+        (cfg!(feature = "bytes"), BYTES),
+        (cfg!(feature = "uuid"), UUID),
+    ]
+    .into_iter()
+    .filter(|(enabled, _)| *enabled)
+    .any(|(_, matches)| extract_generic_type_segment(path, matches).is_some())
+}
+
+// Byte strings converted as a whole by `#[prost(bytes)]` fields: `[u8; N]`,
+// `Vec<u8>` and `Box<[u8]>`.
+pub(crate) fn is_byte_string(ty: &syn::Type) -> bool {
+    let is_u8 = |ty: &syn::Type| extract_type_path(ty).is_some_and(|path| path.is_ident("u8"));
+    if let syn::Type::Array(ref array) = *ty {
+        return is_u8(&array.elem);
+    }
+    if let Some(element) = extract_inner_type_from_container(ty, vec_segment) {
+        return is_u8(element);
+    }
+    if let Some(syn::Type::Slice(slice)) = extract_inner_type_from_container(ty, box_segment) {
+        return is_u8(&slice.elem);
+    }
+    false
+}
+
+// Bytes types of any length, whose conversion by `prost_dto::ProstBytes` from
+// prost can't fail: `Vec<u8>`, `Box<[u8]>` and `bytes::Bytes`.
+pub(crate) fn is_growable_bytes(ty: &syn::Type) -> bool {
+    if let syn::Type::Array(_) = *ty {
+        return false;
+    }
+    is_byte_string(ty)
+        || extract_type_path(ty)
+            .is_some_and(|path| extract_generic_type_segment(path, &["bytes|Bytes|"]).is_some())
+}
+
+// A `Vec<serde_json::Value>`, converted by `#[prost(well_known)]` fields as a
+// whole into a `google.protobuf.ListValue` rather than a repeated `Value`.
+pub(crate) fn is_json_list(ty: &syn::Type) -> bool {
+    cfg!(feature = "serde_json")
+        && extract_inner_type_from_container(ty, vec_segment)
+            .and_then(extract_type_path)
+            .is_some_and(|path| {
+                extract_generic_type_segment(path, &["serde_json|Value|"]).is_some()
+            })
+}
+
+pub(crate) fn is_well_known_type(ty: &syn::Type) -> bool {
+    extract_type_path(ty).and_then(well_known_segment).is_some()
+}
+
 fn extract_type_path(ty: &syn::Type) -> Option<&syn::Path> {
     match *ty {
         syn::Type::Path(ref typepath) if typepath.qself.is_none() => Some(&typepath.path),
@@ -69,6 +285,21 @@ pub(crate) fn extract_inner_type_from_container(
         })
 }
 
+// The value type of a HashMap/BTreeMap, `extract_inner_type_from_container`
+// returns the key.
+pub(crate) fn extract_map_value_type(ty: &syn::Type) -> Option<&syn::Type> {
+    extract_type_path(ty)
+        .and_then(map_segment)
+        .and_then(|path_seg| match path_seg.arguments {
+            PathArguments::AngleBracketed(ref params) => params.args.iter().nth(1),
+            _ => None,
+        })
+        .and_then(|generic_arg| match *generic_arg {
+            GenericArgument::Type(ref ty) => Some(ty),
+            _ => None,
+        })
+}
+
 pub(crate) fn to_snake_case(input: &str) -> String {
     let mut snake = String::new();
     for (i, ch) in input.char_indices() {
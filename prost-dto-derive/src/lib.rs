@@ -12,3 +12,27 @@ pub fn derive_from_prost(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     prost_dto_core::derive_from_prost(input).into()
 }
+
+#[proc_macro_derive(ProstDto, attributes(prost, into_prost, from_prost))]
+pub fn derive_prost_dto(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    prost_dto_core::derive_prost_dto(input).into()
+}
+
+#[proc_macro_derive(TryFromProst, attributes(prost, from_prost))]
+pub fn derive_try_from_prost(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    prost_dto_core::derive_try_from_prost(input).into()
+}
+
+#[proc_macro_derive(ProstPatch, attributes(prost, from_prost))]
+pub fn derive_prost_patch(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    prost_dto_core::derive_prost_patch(input).into()
+}
+
+#[proc_macro_derive(ProstFields, attributes(prost))]
+pub fn derive_prost_fields(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    prost_dto_core::derive_prost_fields(input).into()
+}
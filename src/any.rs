@@ -0,0 +1,21 @@
+//! Packing of DTO enums into `google.protobuf.Any`, used by the code generated
+//! for enums whose variants set `#[prost(type_url = "...")]`.
+
+use prost::Message;
+use prost_types::Any;
+
+use crate::ConversionError;
+
+/// Encodes `message` into a `google.protobuf.Any` with the given type URL.
+pub fn pack_any<M: Message>(type_url: &str, message: &M) -> Any {
+    Any {
+        type_url: type_url.to_owned(),
+        value: message.encode_to_vec(),
+    }
+}
+
+/// Decodes the payload of a `google.protobuf.Any`. The type URL is not
+/// checked, the caller picks `M` by matching it.
+pub fn unpack_any<M: Message + Default>(any: &Any) -> Result<M, ConversionError> {
+    M::decode(any.value.as_slice()).map_err(ConversionError::decode)
+}
@@ -0,0 +1,140 @@
+//! Conversions of `bytes` fields and fixed-size arrays. Fields of
+//! `bytes::Bytes` or `uuid::Uuid` (written as full paths), and byte strings
+//! (e.g. `[u8; 32]`, `Vec<u8>` or `Box<[u8]>`) marked with `#[prost(bytes)]`
+//! are converted as a whole instead of byte by byte.
+//!
+//! Converting from prost into arrays and fixed-length types (e.g.
+//! `uuid::Uuid`) fails on a length mismatch, so it's only supported by
+//! `TryFromProst`.
+
+use crate::ConversionError;
+
+/// Conversion between a Rust type and the prost type `P` of a `bytes` field,
+/// either `Vec<u8>` or `bytes::Bytes`.
+pub trait ProstBytes<P>: Sized {
+    fn into_proto(self) -> P;
+
+    fn try_from_proto(proto: P) -> Result<Self, ConversionError>;
+}
+
+/// Converts the values of a repeated field into an array, failing if there
+/// aren't exactly `N` of them.
+pub fn array_from_vec<T, const N: usize>(values: Vec<T>) -> Result<[T; N], ConversionError> {
+    <[T; N]>::try_from(values).map_err(|values| ConversionError::length_mismatch(N, values.len()))
+}
+
+impl ProstBytes<Vec<u8>> for Vec<u8> {
+    fn into_proto(self) -> Vec<u8> {
+        self
+    }
+
+    fn try_from_proto(proto: Vec<u8>) -> Result<Self, ConversionError> {
+        Ok(proto)
+    }
+}
+
+impl<const N: usize> ProstBytes<Vec<u8>> for [u8; N] {
+    fn into_proto(self) -> Vec<u8> {
+        self.to_vec()
+    }
+
+    fn try_from_proto(proto: Vec<u8>) -> Result<Self, ConversionError> {
+        array_from_vec(proto)
+    }
+}
+
+impl ProstBytes<Vec<u8>> for Box<[u8]> {
+    fn into_proto(self) -> Vec<u8> {
+        self.into_vec()
+    }
+
+    fn try_from_proto(proto: Vec<u8>) -> Result<Self, ConversionError> {
+        Ok(proto.into_boxed_slice())
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl ProstBytes<Vec<u8>> for uuid::Uuid {
+    fn into_proto(self) -> Vec<u8> {
+        self.as_bytes().to_vec()
+    }
+
+    fn try_from_proto(proto: Vec<u8>) -> Result<Self, ConversionError> {
+        uuid::Uuid::from_slice(&proto).map_err(|e| {
+            ConversionError::length_mismatch(size_of::<uuid::Bytes>(), proto.len()).with_source(e)
+        })
+    }
+}
+
+#[cfg(feature = "bytes")]
+mod bytes_impls {
+    use bytes::Bytes;
+
+    use super::ProstBytes;
+    use crate::ConversionError;
+
+    impl ProstBytes<Bytes> for Bytes {
+        fn into_proto(self) -> Bytes {
+            self
+        }
+
+        fn try_from_proto(proto: Bytes) -> Result<Self, ConversionError> {
+            Ok(proto)
+        }
+    }
+
+    impl ProstBytes<Vec<u8>> for Bytes {
+        fn into_proto(self) -> Vec<u8> {
+            self.into()
+        }
+
+        fn try_from_proto(proto: Vec<u8>) -> Result<Self, ConversionError> {
+            Ok(proto.into())
+        }
+    }
+
+    impl ProstBytes<Bytes> for Vec<u8> {
+        fn into_proto(self) -> Bytes {
+            self.into()
+        }
+
+        fn try_from_proto(proto: Bytes) -> Result<Self, ConversionError> {
+            Ok(proto.into())
+        }
+    }
+
+    impl<const N: usize> ProstBytes<Bytes> for [u8; N] {
+        fn into_proto(self) -> Bytes {
+            Bytes::copy_from_slice(&self)
+        }
+
+        fn try_from_proto(proto: Bytes) -> Result<Self, ConversionError> {
+            <[u8; N]>::try_from(proto.as_ref())
+                .map_err(|_| ConversionError::length_mismatch(N, proto.len()))
+        }
+    }
+
+    impl ProstBytes<Bytes> for Box<[u8]> {
+        fn into_proto(self) -> Bytes {
+            self.into()
+        }
+
+        fn try_from_proto(proto: Bytes) -> Result<Self, ConversionError> {
+            Ok(Vec::from(proto).into_boxed_slice())
+        }
+    }
+
+    #[cfg(feature = "uuid")]
+    impl ProstBytes<Bytes> for uuid::Uuid {
+        fn into_proto(self) -> Bytes {
+            Bytes::copy_from_slice(self.as_bytes())
+        }
+
+        fn try_from_proto(proto: Bytes) -> Result<Self, ConversionError> {
+            uuid::Uuid::from_slice(&proto).map_err(|e| {
+                ConversionError::length_mismatch(size_of::<uuid::Bytes>(), proto.len())
+                    .with_source(e)
+            })
+        }
+    }
+}
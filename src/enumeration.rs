@@ -0,0 +1,43 @@
+use crate::ConversionError;
+
+/// Conversion from the raw `i32` value of a prost enumeration.
+///
+/// Implemented by `FromProst` and `TryFromProst` for unit enums, in addition
+/// to `TryFrom<i32>`. Used by `TryFromProst` for `Option<T>` fields with
+/// `#[prost(enumeration = "...")]`.
+pub trait ProstEnumeration: Sized {
+    /// Converts the value, the sentinel variant of the enumeration (`Unknown`
+    /// by default, see `#[prost(unknown = "...")]`) is treated as unset.
+    fn try_from_i32_or_none(value: i32) -> Result<Option<Self>, ConversionError>;
+}
+
+/// The repr of an optional enumeration field in a prost message.
+///
+/// Proto3 enumeration fields are `i32` where zero (the default) is unset, or
+/// `Option<i32>` for `optional` fields. Whether zero is unset depends on the
+/// enumeration, so `i32` values are always read, see `ProstEnumeration`.
+pub trait EnumerationRepr {
+    fn into_option(self) -> Option<i32>;
+
+    fn from_option(value: Option<i32>) -> Self;
+}
+
+impl EnumerationRepr for i32 {
+    fn into_option(self) -> Option<i32> {
+        Some(self)
+    }
+
+    fn from_option(value: Option<i32>) -> Self {
+        value.unwrap_or_default()
+    }
+}
+
+impl EnumerationRepr for Option<i32> {
+    fn into_option(self) -> Option<i32> {
+        self
+    }
+
+    fn from_option(value: Option<i32>) -> Self {
+        value
+    }
+}
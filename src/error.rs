@@ -0,0 +1,237 @@
+use std::convert::Infallible;
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter, Write};
+
+type BoxError = Box<dyn Error + Send + Sync + 'static>;
+
+/// The error returned by conversions generated with `TryFromProst`.
+///
+/// Besides the failure [`ErrorKind`], the error records the path of the field
+/// that failed through nested messages, repeated fields and maps, e.g.
+/// `schedule.run_at.timepoints[3]`.
+#[derive(Debug)]
+pub struct ConversionError {
+    kind: ErrorKind,
+    // Segments are pushed as the error bubbles up, so they are stored from the
+    // innermost to the outermost.
+    path: Vec<PathSegment>,
+    source: Option<BoxError>,
+}
+
+/// Why a conversion from a prost type failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// A `#[prost(required)]` field was not set.
+    MissingRequired,
+    /// None of the variants of a oneof were set.
+    MissingOneof,
+    /// The value is not a known variant of the prost enumeration.
+    UnknownEnumValue { enum_name: &'static str, value: i32 },
+    /// The prost variant has no counterpart in the target type.
+    UnsupportedVariant { type_name: &'static str },
+    /// A `try_map` function failed, the error is available as the source.
+    Mapper,
+    /// The value of a well-known type can't be represented by the target type.
+    OutOfRange { type_name: &'static str },
+    /// The type URL of a `google.protobuf.Any` has no match in the target type.
+    UnknownTypeUrl { type_url: String },
+    /// The payload of a `google.protobuf.Any` failed to decode, the error is
+    /// available as the source.
+    Decode,
+    /// The path of a `google.protobuf.FieldMask` doesn't name a field.
+    UnknownMaskPath { path: String },
+    /// A repeated field holds the same value more than once, and the target
+    /// set denies duplicates.
+    DuplicateValue,
+    /// A repeated or bytes field doesn't have the length of the target array.
+    LengthMismatch { expected: usize, actual: usize },
+}
+
+/// A single step in the path of a field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathSegment {
+    /// A (prost) field name.
+    Field(&'static str),
+    /// An index in a repeated field.
+    Index(usize),
+    /// A key in a map field, rendered with `Debug`: string keys are quoted,
+    /// integer and bool keys aren't.
+    Key(String),
+}
+
+impl ConversionError {
+    pub fn new(kind: ErrorKind) -> Self {
+        Self {
+            kind,
+            path: Vec::new(),
+            source: None,
+        }
+    }
+
+    pub fn missing_required(field: &'static str) -> Self {
+        Self::new(ErrorKind::MissingRequired).at_field(field)
+    }
+
+    pub fn missing_oneof(field: &'static str) -> Self {
+        Self::new(ErrorKind::MissingOneof).at_field(field)
+    }
+
+    pub fn unknown_enum_value(enum_name: &'static str, value: i32) -> Self {
+        Self::new(ErrorKind::UnknownEnumValue { enum_name, value })
+    }
+
+    pub fn unsupported_variant(type_name: &'static str) -> Self {
+        Self::new(ErrorKind::UnsupportedVariant { type_name })
+    }
+
+    pub fn mapper(source: impl Into<BoxError>) -> Self {
+        Self::new(ErrorKind::Mapper).with_source(source)
+    }
+
+    pub fn out_of_range(type_name: &'static str) -> Self {
+        Self::new(ErrorKind::OutOfRange { type_name })
+    }
+
+    pub fn unknown_type_url(type_url: impl Into<String>) -> Self {
+        Self::new(ErrorKind::UnknownTypeUrl {
+            type_url: type_url.into(),
+        })
+    }
+
+    pub fn decode(source: impl Into<BoxError>) -> Self {
+        Self::new(ErrorKind::Decode).with_source(source)
+    }
+
+    pub fn unknown_mask_path(path: impl Into<String>) -> Self {
+        Self::new(ErrorKind::UnknownMaskPath { path: path.into() })
+    }
+
+    pub fn duplicate_value() -> Self {
+        Self::new(ErrorKind::DuplicateValue)
+    }
+
+    pub fn length_mismatch(expected: usize, actual: usize) -> Self {
+        Self::new(ErrorKind::LengthMismatch { expected, actual })
+    }
+
+    /// Attaches the underlying error.
+    pub fn with_source(mut self, source: impl Into<BoxError>) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+
+    /// Prepends a field name to the path.
+    pub fn at_field(mut self, field: &'static str) -> Self {
+        self.path.push(PathSegment::Field(field));
+        self
+    }
+
+    /// Prepends an index in a repeated field to the path.
+    pub fn at_index(mut self, index: usize) -> Self {
+        self.path.push(PathSegment::Index(index));
+        self
+    }
+
+    /// Prepends a key in a map field to the path.
+    pub fn at_key(mut self, key: &impl Debug) -> Self {
+        self.path.push(PathSegment::Key(format!("{key:?}")));
+        self
+    }
+
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+
+    /// The segments of the path from the outermost to the innermost.
+    pub fn segments(&self) -> impl Iterator<Item = &PathSegment> {
+        self.path.iter().rev()
+    }
+
+    /// The name of the innermost field that failed to convert, if known.
+    pub fn field(&self) -> Option<&'static str> {
+        self.path.iter().find_map(|segment| match segment {
+            PathSegment::Field(field) => Some(*field),
+            _ => None,
+        })
+    }
+
+    /// The dotted path of the field that failed, e.g.
+    /// `schedule.run_at.timepoints[3]`. Empty if the error is not tied to a
+    /// field.
+    pub fn path(&self) -> String {
+        let mut path = String::new();
+        for segment in self.segments() {
+            match segment {
+                PathSegment::Field(field) => {
+                    if !path.is_empty() {
+                        path.push('.');
+                    }
+                    path.push_str(field);
+                }
+                PathSegment::Index(index) => {
+                    let _ = write!(path, "[{index}]");
+                }
+                PathSegment::Key(key) => {
+                    let _ = write!(path, "[{key}]");
+                }
+            }
+        }
+        path
+    }
+}
+
+impl Display for ErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ErrorKind::MissingRequired => write!(f, "missing required field"),
+            ErrorKind::MissingOneof => write!(f, "missing oneof"),
+            ErrorKind::UnknownEnumValue { enum_name, value } => {
+                write!(f, "unknown value {value} for enum `{enum_name}`")
+            }
+            ErrorKind::UnsupportedVariant { type_name } => {
+                write!(
+                    f,
+                    "variant of `{type_name}` has no match in the target type"
+                )
+            }
+            ErrorKind::Mapper => write!(f, "mapper failed"),
+            ErrorKind::OutOfRange { type_name } => {
+                write!(f, "value out of range for `{type_name}`")
+            }
+            ErrorKind::UnknownTypeUrl { type_url } => write!(f, "unknown type URL `{type_url}`"),
+            ErrorKind::Decode => write!(f, "failed to decode message"),
+            ErrorKind::UnknownMaskPath { path } => write!(f, "unknown field mask path `{path}`"),
+            ErrorKind::DuplicateValue => write!(f, "duplicate value in set"),
+            ErrorKind::LengthMismatch { expected, actual } => {
+                write!(f, "expected {expected} elements, got {actual}")
+            }
+        }
+    }
+}
+
+impl Display for ConversionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if !self.path.is_empty() {
+            write!(f, "{}: ", self.path())?;
+        }
+        write!(f, "{}", self.kind)?;
+        if let Some(ref source) = self.source {
+            write!(f, ": {source}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for ConversionError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source.as_deref().map(|e| e as _)
+    }
+}
+
+// Allows `?` on conversions backed by an infallible `From` impl.
+impl From<Infallible> for ConversionError {
+    fn from(value: Infallible) -> Self {
+        match value {}
+    }
+}
@@ -0,0 +1,86 @@
+//! Proto field names and typed field mask paths, generated by
+//! `#[derive(ProstFields)]` alongside the conversion derives, e.g.
+//! `#[derive(IntoProst, FromProst, ProstFields)]`, from the same
+//! `#[prost(...)]` attributes.
+//!
+//! It's a derive of its own rather than part of `IntoProst` and `FromProst`:
+//! DTOs often derive both, and each would emit the `ProstFields` impl and the
+//! paths struct.
+
+use std::fmt::{Display, Formatter};
+
+/// Lists the proto field names of a DTO, honoring `#[prost(name)]` renames
+/// and skipping `#[prost(skip)]` fields.
+pub trait ProstFields {
+    /// The proto field names, in declaration order.
+    const PROTO_FIELDS: &'static [&'static str];
+
+    /// The generated accessors of the field paths, e.g. `UserFieldPaths`.
+    type Paths: From<FieldPath>;
+
+    fn field_paths() -> Self::Paths {
+        FieldPath::default().into()
+    }
+}
+
+/// A dotted path of proto field names, e.g. `schedule.run_at`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct FieldPath(String);
+
+impl FieldPath {
+    /// Appends a field to the path.
+    pub fn join(&self, field: &str) -> FieldPath {
+        if self.0.is_empty() {
+            FieldPath(field.to_owned())
+        } else {
+            FieldPath(format!("{}.{field}", self.0))
+        }
+    }
+
+    /// Continues the path into a nested message of type `T`.
+    pub fn nested<T: ProstFields>(self) -> T::Paths {
+        self.into()
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for FieldPath {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<FieldPath> for String {
+    fn from(value: FieldPath) -> Self {
+        value.0
+    }
+}
+
+/// Collects field paths into a `google.protobuf.FieldMask`.
+#[derive(Debug, Clone, Default)]
+pub struct FieldMaskBuilder {
+    paths: Vec<String>,
+}
+
+impl FieldMaskBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn path(mut self, path: FieldPath) -> Self {
+        self.paths.push(path.0);
+        self
+    }
+
+    pub fn paths(&self) -> &[String] {
+        &self.paths
+    }
+
+    #[cfg(feature = "prost-types")]
+    pub fn build(self) -> prost_types::FieldMask {
+        prost_types::FieldMask { paths: self.paths }
+    }
+}
@@ -1 +1,23 @@
+#[cfg(feature = "prost-types")]
+mod any;
+mod binary;
+mod enumeration;
+mod error;
+mod fields;
+#[cfg(feature = "prost-types")]
+mod patch;
+#[cfg(feature = "prost-types")]
+mod well_known;
+
 pub use prost_dto_derive::*;
+
+#[cfg(feature = "prost-types")]
+pub use self::any::{pack_any, unpack_any};
+pub use self::binary::{ProstBytes, array_from_vec};
+pub use self::enumeration::{EnumerationRepr, ProstEnumeration};
+pub use self::error::{ConversionError, ErrorKind, PathSegment};
+pub use self::fields::{FieldMaskBuilder, FieldPath, ProstFields};
+#[cfg(feature = "prost-types")]
+pub use self::patch::ProstPatch;
+#[cfg(feature = "prost-types")]
+pub use self::well_known::WellKnown;
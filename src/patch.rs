@@ -0,0 +1,44 @@
+//! Partial updates driven by a `google.protobuf.FieldMask`, implemented by
+//! `#[derive(ProstPatch)]`.
+
+use prost_types::FieldMask;
+
+use crate::ConversionError;
+
+/// Applies the fields of a prost message selected by a field mask.
+///
+/// Mask paths are the proto field names, nested messages are addressed with
+/// dotted paths (e.g. `address.city`) when the field is marked with
+/// `#[prost(patch)]`.
+pub trait ProstPatch {
+    /// The prost message carrying the new values.
+    type Proto;
+
+    /// The valid top-level mask paths. Paths into nested messages are checked
+    /// with [`ProstPatch::is_valid_mask_path`].
+    const MASK_PATHS: &'static [&'static str];
+
+    fn is_valid_mask_path(path: &str) -> bool;
+
+    /// Copies the values of `paths` from `proto`, the paths are not validated.
+    fn apply_proto_paths(
+        &mut self,
+        proto: Self::Proto,
+        paths: &[&str],
+    ) -> Result<(), ConversionError>;
+
+    /// Copies the values of the paths in `mask` from `proto`. Fails without
+    /// touching `self` if a path is not valid. A failed conversion may leave
+    /// the fields before it updated.
+    fn apply_proto_with_mask(
+        &mut self,
+        proto: Self::Proto,
+        mask: &FieldMask,
+    ) -> Result<(), ConversionError> {
+        if let Some(path) = mask.paths.iter().find(|p| !Self::is_valid_mask_path(p)) {
+            return Err(ConversionError::unknown_mask_path(path.as_str()));
+        }
+        let paths: Vec<&str> = mask.paths.iter().map(String::as_str).collect();
+        self.apply_proto_paths(proto, &paths)
+    }
+}
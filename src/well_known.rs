@@ -0,0 +1,208 @@
+//! Conversions between the protobuf well-known types and their Rust
+//! counterparts. Fields of these types written as full paths (e.g.
+//! `std::time::SystemTime` or `chrono::DateTime<Utc>`) are converted
+//! automatically, others need `#[prost(well_known)]` (e.g. a `Duration`
+//! imported from `std::time`). Converting from protobuf can fail, so it's
+//! only supported by TryFromProst.
+//!
+//! A `Vec<serde_json::Value>` is a repeated `google.protobuf.Value`, and a
+//! `google.protobuf.ListValue` with `#[prost(well_known)]`.
+
+use std::time::SystemTime;
+
+use prost_types::{Duration, Timestamp};
+
+use crate::ConversionError;
+
+/// Conversion between a Rust type and the prost type `P` of a protobuf
+/// well-known type.
+pub trait WellKnown<P>: Sized {
+    fn into_proto(self) -> P;
+
+    fn try_from_proto(proto: P) -> Result<Self, ConversionError>;
+}
+
+// The range of `google.protobuf.Timestamp`, from 0001-01-01T00:00:00Z to
+// 9999-12-31T23:59:59.999999999Z.
+const MIN_TIMESTAMP_SECONDS: i64 = -62_135_596_800;
+const MAX_TIMESTAMP_SECONDS: i64 = 253_402_300_799;
+// The range of `google.protobuf.Duration`, about 10,000 years.
+const MAX_DURATION_SECONDS: u64 = 315_576_000_000;
+
+// Clamps a normalized timestamp to the range of `google.protobuf.Timestamp`.
+fn clamp_timestamp(timestamp: Timestamp) -> Timestamp {
+    if timestamp.seconds < MIN_TIMESTAMP_SECONDS {
+        Timestamp {
+            seconds: MIN_TIMESTAMP_SECONDS,
+            nanos: 0,
+        }
+    } else if timestamp.seconds > MAX_TIMESTAMP_SECONDS {
+        Timestamp {
+            seconds: MAX_TIMESTAMP_SECONDS,
+            nanos: 999_999_999,
+        }
+    } else {
+        timestamp
+    }
+}
+
+/// Times before year 1 or after year 9999, beyond the range of
+/// `google.protobuf.Timestamp`, are clamped to it.
+impl WellKnown<Timestamp> for SystemTime {
+    fn into_proto(self) -> Timestamp {
+        clamp_timestamp(self.into())
+    }
+
+    fn try_from_proto(proto: Timestamp) -> Result<Self, ConversionError> {
+        SystemTime::try_from(proto)
+            .map_err(|e| ConversionError::out_of_range("google.protobuf.Timestamp").with_source(e))
+    }
+}
+
+/// Durations beyond 315,576,000,000 seconds, the maximum of
+/// `google.protobuf.Duration`, are clamped to it.
+impl WellKnown<Duration> for std::time::Duration {
+    fn into_proto(self) -> Duration {
+        if self.as_secs() > MAX_DURATION_SECONDS {
+            return Duration {
+                seconds: MAX_DURATION_SECONDS as i64,
+                nanos: 999_999_999,
+            };
+        }
+        Duration {
+            seconds: self.as_secs() as i64,
+            nanos: self.subsec_nanos() as i32,
+        }
+    }
+
+    // Negative durations are rejected.
+    fn try_from_proto(proto: Duration) -> Result<Self, ConversionError> {
+        std::time::Duration::try_from(proto)
+            .map_err(|e| ConversionError::out_of_range("google.protobuf.Duration").with_source(e))
+    }
+}
+
+/// Times before year 1 or after year 9999, beyond the range of
+/// `google.protobuf.Timestamp`, are clamped to it.
+#[cfg(feature = "chrono")]
+impl WellKnown<Timestamp> for chrono::DateTime<chrono::Utc> {
+    // During a leap second, chrono reports nanos beyond 1e9.
+    fn into_proto(self) -> Timestamp {
+        let mut timestamp = Timestamp {
+            seconds: self.timestamp(),
+            nanos: self.timestamp_subsec_nanos() as i32,
+        };
+        timestamp.normalize();
+        clamp_timestamp(timestamp)
+    }
+
+    fn try_from_proto(mut proto: Timestamp) -> Result<Self, ConversionError> {
+        proto.normalize();
+        chrono::DateTime::from_timestamp(proto.seconds, proto.nanos as u32)
+            .ok_or_else(|| ConversionError::out_of_range("google.protobuf.Timestamp"))
+    }
+}
+
+/// Times before year 1, beyond the range of `google.protobuf.Timestamp`, are
+/// clamped to it.
+#[cfg(feature = "time")]
+impl WellKnown<Timestamp> for time::OffsetDateTime {
+    fn into_proto(self) -> Timestamp {
+        clamp_timestamp(Timestamp {
+            seconds: self.unix_timestamp(),
+            nanos: self.nanosecond() as i32,
+        })
+    }
+
+    fn try_from_proto(proto: Timestamp) -> Result<Self, ConversionError> {
+        let nanos = i128::from(proto.seconds) * 1_000_000_000 + i128::from(proto.nanos);
+        time::OffsetDateTime::from_unix_timestamp_nanos(nanos)
+            .map_err(|e| ConversionError::out_of_range("google.protobuf.Timestamp").with_source(e))
+    }
+}
+
+#[cfg(feature = "serde_json")]
+mod json {
+    use prost_types::value::Kind;
+    use prost_types::{ListValue, Struct, Value};
+
+    use super::WellKnown;
+    use crate::ConversionError;
+
+    // Integral numbers in this range are exactly representable as f64, they
+    // are converted back to JSON integers.
+    const MAX_SAFE_INTEGER: f64 = 9_007_199_254_740_991.0;
+
+    impl WellKnown<Value> for serde_json::Value {
+        // Numbers are converted to f64 since that's what protobuf supports,
+        // integers beyond 2^53 lose precision.
+        fn into_proto(self) -> Value {
+            let kind = match self {
+                serde_json::Value::Null => Kind::NullValue(0),
+                serde_json::Value::Bool(b) => Kind::BoolValue(b),
+                serde_json::Value::Number(n) => Kind::NumberValue(n.as_f64().unwrap_or(f64::NAN)),
+                serde_json::Value::String(s) => Kind::StringValue(s),
+                serde_json::Value::Array(values) => Kind::ListValue(values.into_proto()),
+                serde_json::Value::Object(map) => Kind::StructValue(map.into_proto()),
+            };
+            Value { kind: Some(kind) }
+        }
+
+        // NaN and infinite numbers can't be represented in JSON.
+        fn try_from_proto(proto: Value) -> Result<Self, ConversionError> {
+            Ok(match proto.kind {
+                None | Some(Kind::NullValue(_)) => serde_json::Value::Null,
+                Some(Kind::BoolValue(b)) => serde_json::Value::Bool(b),
+                Some(Kind::NumberValue(n)) if n.fract() == 0.0 && n.abs() <= MAX_SAFE_INTEGER => {
+                    serde_json::Value::Number((n as i64).into())
+                }
+                Some(Kind::NumberValue(n)) => serde_json::Number::from_f64(n)
+                    .map(serde_json::Value::Number)
+                    .ok_or_else(|| ConversionError::out_of_range("google.protobuf.Value"))?,
+                Some(Kind::StringValue(s)) => serde_json::Value::String(s),
+                Some(Kind::ListValue(list)) => {
+                    serde_json::Value::Array(WellKnown::try_from_proto(list)?)
+                }
+                Some(Kind::StructValue(s)) => {
+                    serde_json::Value::Object(WellKnown::try_from_proto(s)?)
+                }
+            })
+        }
+    }
+
+    impl WellKnown<ListValue> for Vec<serde_json::Value> {
+        fn into_proto(self) -> ListValue {
+            ListValue {
+                values: self.into_iter().map(WellKnown::into_proto).collect(),
+            }
+        }
+
+        fn try_from_proto(proto: ListValue) -> Result<Self, ConversionError> {
+            proto
+                .values
+                .into_iter()
+                .enumerate()
+                .map(|(i, v)| WellKnown::try_from_proto(v).map_err(|e| e.at_index(i)))
+                .collect()
+        }
+    }
+
+    impl WellKnown<Struct> for serde_json::Map<String, serde_json::Value> {
+        fn into_proto(self) -> Struct {
+            Struct {
+                fields: self.into_iter().map(|(k, v)| (k, v.into_proto())).collect(),
+            }
+        }
+
+        fn try_from_proto(proto: Struct) -> Result<Self, ConversionError> {
+            proto
+                .fields
+                .into_iter()
+                .map(|(k, v)| match WellKnown::try_from_proto(v) {
+                    Ok(v) => Ok((k, v)),
+                    Err(e) => Err(e.at_key(&k)),
+                })
+                .collect()
+        }
+    }
+}
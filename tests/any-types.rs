@@ -0,0 +1,99 @@
+use prost_dto::{ErrorKind, FromProst, IntoProst, TryFromProst};
+
+mod proto {
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Click {
+        #[prost(int32, tag = "1")]
+        pub x: i32,
+        #[prost(int32, tag = "2")]
+        pub y: i32,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Key {
+        #[prost(string, tag = "1")]
+        pub code: String,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Event {
+        pub id: u64,
+        pub payload: Option<prost_types::Any>,
+        pub history: Vec<prost_types::Any>,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, IntoProst, FromProst)]
+#[prost(target = "proto::Click")]
+pub struct Click {
+    pub x: i32,
+    pub y: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, IntoProst, FromProst)]
+#[prost(target = "proto::Key")]
+pub struct Key {
+    pub code: String,
+}
+
+#[derive(Debug, Clone, PartialEq, IntoProst, TryFromProst)]
+#[prost(target = "prost_types::Any")]
+pub enum Payload {
+    #[prost(target = "proto::Click", type_url = "type.googleapis.com/acme.Click")]
+    Click(Click),
+    #[prost(target = "proto::Key", type_url = "type.googleapis.com/acme.Key")]
+    Key(Key),
+}
+
+#[derive(Debug, Clone, PartialEq, IntoProst, TryFromProst)]
+#[prost(target = "proto::Event")]
+pub struct Event {
+    pub id: u64,
+    #[prost(any)]
+    pub payload: Payload,
+    pub history: Vec<Payload>,
+}
+
+fn main() {
+    let event = Event {
+        id: 1,
+        payload: Payload::Click(Click { x: 3, y: 4 }),
+        history: vec![Payload::Key(Key {
+            code: "Enter".to_owned(),
+        })],
+    };
+    let proto: proto::Event = event.clone().into();
+    let payload = proto.payload.clone().unwrap();
+    assert_eq!(payload.type_url, "type.googleapis.com/acme.Click");
+    assert_eq!(
+        prost_dto::unpack_any::<proto::Click>(&payload).unwrap(),
+        proto::Click { x: 3, y: 4 }
+    );
+    assert_eq!(proto.history[0].type_url, "type.googleapis.com/acme.Key");
+
+    let back = Event::try_from(proto.clone()).unwrap();
+    assert_eq!(back, event);
+
+    let mut unknown = proto.clone();
+    unknown.payload.as_mut().unwrap().type_url = "type.googleapis.com/acme.Scroll".to_owned();
+    let err = Event::try_from(unknown).unwrap_err();
+    assert_eq!(
+        err.kind(),
+        &ErrorKind::UnknownTypeUrl {
+            type_url: "type.googleapis.com/acme.Scroll".to_owned()
+        }
+    );
+    assert_eq!(err.path(), "payload");
+
+    let mut corrupt = proto.clone();
+    corrupt.history[0].value = vec![0xff];
+    let err = Event::try_from(corrupt).unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::Decode);
+    assert_eq!(err.path(), "history[0]");
+
+    let mut missing = proto;
+    missing.payload = None;
+    let err = Event::try_from(missing).unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::MissingRequired);
+    assert_eq!(err.path(), "payload");
+}
@@ -0,0 +1,160 @@
+use prost_dto::{ErrorKind, FromProst, IntoProst, TryFromProst};
+
+mod proto {
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Block {
+        pub hash: Vec<u8>,
+        pub parents: Vec<Vec<u8>>,
+        pub digest: Option<Vec<u8>>,
+        pub payload: Vec<u8>,
+        pub blob: Vec<u8>,
+        pub coords: Vec<i64>,
+        pub request_id: String,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Histogram {
+        pub levels: Vec<u32>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Frame {
+        pub payload: Vec<u8>,
+    }
+}
+
+// Not a `uuid::Uuid`, converted with `Into`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Uuid(String);
+
+impl From<Uuid> for String {
+    fn from(id: Uuid) -> Self {
+        id.0
+    }
+}
+
+impl From<String> for Uuid {
+    fn from(id: String) -> Self {
+        Self(id)
+    }
+}
+
+// Arrays are converted from prost by TryFromProst only, see `CheckedBlock`.
+#[derive(Debug, Clone, PartialEq, IntoProst)]
+#[prost(target = "proto::Block")]
+pub struct Block {
+    #[prost(bytes)]
+    pub hash: [u8; 32],
+    pub parents: Vec<[u8; 4]>,
+    #[prost(bytes)]
+    pub digest: Option<[u8; 4]>,
+    pub payload: Vec<u8>,
+    pub blob: Box<[u8]>,
+    pub coords: [i64; 3],
+    pub request_id: Uuid,
+}
+
+#[derive(Debug, Clone, PartialEq, TryFromProst)]
+#[prost(target = "proto::Block")]
+pub struct CheckedBlock {
+    #[prost(bytes)]
+    pub hash: [u8; 32],
+    pub parents: Vec<[u8; 4]>,
+    #[prost(bytes)]
+    pub digest: Option<[u8; 4]>,
+    pub payload: Vec<u8>,
+    pub blob: Box<[u8]>,
+    pub coords: [i64; 3],
+    pub request_id: Uuid,
+}
+
+// Byte strings are converted byte by byte unless marked with `bytes`.
+#[derive(Debug, Clone, PartialEq, IntoProst)]
+#[prost(target = "proto::Histogram")]
+pub struct Histogram {
+    pub levels: Vec<u8>,
+}
+
+// Mappers of `bytes` fields get the whole value.
+#[derive(Debug, Clone, PartialEq, IntoProst, FromProst)]
+#[prost(target = "proto::Frame")]
+pub struct Frame {
+    #[prost(bytes)]
+    #[into_prost(map = "add_length")]
+    #[from_prost(map = "strip_length")]
+    pub payload: Vec<u8>,
+}
+
+fn add_length(payload: Vec<u8>) -> Vec<u8> {
+    let mut framed = vec![payload.len() as u8];
+    framed.extend(payload);
+    framed
+}
+
+fn strip_length(framed: Vec<u8>) -> Vec<u8> {
+    framed[1..].to_vec()
+}
+
+fn main() {
+    let block = Block {
+        hash: [7; 32],
+        parents: vec![[1, 2, 3, 4]],
+        digest: Some([9; 4]),
+        payload: b"payload".to_vec(),
+        blob: Box::from(&b"blob"[..]),
+        coords: [1, -2, 3],
+        request_id: Uuid("req-1".to_owned()),
+    };
+    let proto: proto::Block = block.clone().into();
+    assert_eq!(proto.hash, vec![7; 32]);
+    assert_eq!(proto.parents, vec![vec![1, 2, 3, 4]]);
+    assert_eq!(proto.digest, Some(vec![9; 4]));
+    assert_eq!(proto.blob, b"blob".to_vec());
+    assert_eq!(proto.coords, vec![1, -2, 3]);
+    assert_eq!(proto.request_id, "req-1");
+
+    let checked = CheckedBlock::try_from(proto.clone()).unwrap();
+    assert_eq!(checked.hash, block.hash);
+    assert_eq!(checked.parents, block.parents);
+    assert_eq!(checked.digest, block.digest);
+    assert_eq!(checked.payload, block.payload);
+    assert_eq!(checked.blob, block.blob);
+    assert_eq!(checked.coords, block.coords);
+    assert_eq!(checked.request_id, block.request_id);
+
+    let mut short = proto.clone();
+    short.hash.truncate(3);
+    let err = CheckedBlock::try_from(short).unwrap_err();
+    assert_eq!(
+        err.kind(),
+        &ErrorKind::LengthMismatch {
+            expected: 32,
+            actual: 3
+        }
+    );
+    assert_eq!(err.path(), "hash");
+
+    let mut parents = proto.clone();
+    parents.parents.push(vec![1]);
+    let err = CheckedBlock::try_from(parents).unwrap_err();
+    assert_eq!(err.path(), "parents[1]");
+
+    let mut missing = proto;
+    missing.coords.pop();
+    let err = CheckedBlock::try_from(missing).unwrap_err();
+    assert_eq!(err.path(), "coords");
+
+    let histogram = Histogram {
+        levels: vec![1, 255],
+    };
+    let proto: proto::Histogram = histogram.into();
+    assert_eq!(proto.levels, vec![1, 255]);
+
+    let frame = Frame {
+        payload: b"abc".to_vec(),
+    };
+    let proto: proto::Frame = frame.clone().into();
+    assert_eq!(proto.payload, b"\x03abc".to_vec());
+    let back: Frame = proto.into();
+    assert_eq!(back, frame);
+}
@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use prost_dto::{FromProst, IntoProst, TryFromProst};
+
+mod proto {
+    use std::collections::HashMap;
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Point {
+        pub x: i32,
+        pub y: i32,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Shape {
+        pub name: String,
+        pub origin: Option<Point>,
+        pub points: Vec<Point>,
+        pub labels: HashMap<String, String>,
+        pub tags: Vec<String>,
+        pub area: u64,
+        pub comment: Option<String>,
+        pub aliases: Vec<String>,
+        pub parent: Option<Point>,
+        pub attributes: HashMap<String, String>,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, IntoProst, FromProst)]
+#[prost(target = "proto::Point", by_ref)]
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Name(String);
+
+fn name_to_proto(name: &Name) -> String {
+    name.0.to_uppercase()
+}
+
+fn name_from_proto(name: &str) -> Name {
+    Name(name.to_lowercase())
+}
+
+#[derive(Debug, Clone, PartialEq, IntoProst, FromProst)]
+#[prost(target = "proto::Shape", by_ref)]
+pub struct Shape {
+    #[into_prost(map = "name_to_proto", map_by_ref)]
+    #[from_prost(map = "name_from_proto", map_by_ref)]
+    pub name: Name,
+    #[prost(required, by_ref)]
+    pub origin: Point,
+    #[prost(by_ref)]
+    pub points: Vec<Point>,
+    pub labels: HashMap<String, String>,
+    pub tags: Vec<String>,
+    pub area: u64,
+    pub comment: Option<String>,
+    // Nested containers and entries are iterated by reference too.
+    pub aliases: Option<Vec<String>>,
+    #[prost(by_ref)]
+    pub parent: Option<Arc<Point>>,
+    #[prost(map_entries)]
+    pub attributes: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone, PartialEq, TryFromProst)]
+#[prost(target = "proto::Shape", by_ref)]
+pub struct CheckedShape {
+    #[prost(skip)]
+    pub name: String,
+    #[prost(required)]
+    pub origin: proto::Point,
+    pub points: Vec<proto::Point>,
+    pub labels: HashMap<String, String>,
+    #[from_prost(try_map = "u8::try_from")]
+    pub area: u8,
+    pub comment: Option<String>,
+    pub aliases: Option<Vec<String>>,
+    #[prost(map_entries)]
+    pub attributes: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone, PartialEq, IntoProst, FromProst)]
+#[prost(target = "proto::Point", transparent, by_ref)]
+pub struct Origin(#[prost(by_ref)] Point);
+
+fn main() {
+    let shape = Shape {
+        name: Name("square".to_owned()),
+        origin: Point { x: 1, y: 2 },
+        points: vec![Point { x: 3, y: 4 }],
+        labels: HashMap::from([("color".to_owned(), "red".to_owned())]),
+        tags: vec!["a".to_owned()],
+        area: 16,
+        comment: Some("nice".to_owned()),
+        aliases: Some(vec!["box".to_owned()]),
+        parent: Some(Arc::new(Point { x: 0, y: 0 })),
+        attributes: vec![("layer".to_owned(), "top".to_owned())],
+    };
+
+    // The DTO is still usable after converting it.
+    let proto = proto::Shape::from(&shape);
+    assert_eq!(proto.name, "SQUARE");
+    assert_eq!(proto.origin, Some(proto::Point { x: 1, y: 2 }));
+    assert_eq!(proto.points, vec![proto::Point { x: 3, y: 4 }]);
+    assert_eq!(proto.labels["color"], "red");
+    assert_eq!(proto.aliases, vec!["box"]);
+    assert_eq!(proto.parent, Some(proto::Point { x: 0, y: 0 }));
+    assert_eq!(proto.attributes["layer"], "top");
+    assert_eq!(proto, proto::Shape::from(shape.clone()));
+
+    let back = Shape::from(&proto);
+    assert_eq!(back, shape);
+
+    let checked = CheckedShape::try_from(&proto).unwrap();
+    assert_eq!(checked.area, 16);
+    assert_eq!(checked.origin, proto::Point { x: 1, y: 2 });
+    assert_eq!(checked.aliases, shape.aliases);
+    assert_eq!(checked.attributes, shape.attributes);
+
+    let mut big = proto.clone();
+    big.area = 1000;
+    let err = CheckedShape::try_from(&big).unwrap_err();
+    assert_eq!(err.path(), "area");
+
+    let origin = Origin(Point { x: 5, y: 6 });
+    assert_eq!(proto::Point::from(&origin), proto::Point { x: 5, y: 6 });
+    assert_eq!(Origin::from(&proto::Point { x: 5, y: 6 }), origin);
+}
@@ -0,0 +1,98 @@
+use bytes::Bytes;
+use prost_dto::{ErrorKind, FromProst, IntoProst, TryFromProst};
+
+mod proto {
+    use bytes::Bytes;
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Object {
+        pub id: Vec<u8>,
+        pub owner: Bytes,
+        pub checksum: Bytes,
+        pub content: Bytes,
+        pub raw: Vec<u8>,
+        pub chunks: Vec<Bytes>,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, IntoProst)]
+#[prost(target = "proto::Object")]
+pub struct Object {
+    pub id: uuid::Uuid,
+    pub owner: uuid::Uuid,
+    #[prost(bytes)]
+    pub checksum: [u8; 4],
+    #[prost(bytes)]
+    pub content: Vec<u8>,
+    pub raw: bytes::Bytes,
+    #[prost(bytes)]
+    pub chunks: Vec<Box<[u8]>>,
+}
+
+// Bytes types of any length are also converted by FromProst.
+#[derive(Debug, Clone, PartialEq, FromProst)]
+#[prost(target = "proto::Object")]
+pub struct ObjectData {
+    #[prost(bytes)]
+    pub id: Vec<u8>,
+    pub owner: bytes::Bytes,
+    pub checksum: bytes::Bytes,
+    #[prost(bytes)]
+    pub content: Vec<u8>,
+    #[prost(bytes)]
+    pub raw: Box<[u8]>,
+    pub chunks: Vec<bytes::Bytes>,
+}
+
+#[derive(Debug, Clone, PartialEq, TryFromProst)]
+#[prost(target = "proto::Object")]
+pub struct CheckedObject {
+    pub id: uuid::Uuid,
+    pub owner: uuid::Uuid,
+    #[prost(bytes)]
+    pub checksum: [u8; 4],
+    pub content: bytes::Bytes,
+    #[prost(bytes)]
+    pub raw: Box<[u8]>,
+    pub chunks: Vec<bytes::Bytes>,
+}
+
+fn main() {
+    let object = Object {
+        id: uuid::Uuid::from_bytes([1; 16]),
+        owner: uuid::Uuid::from_bytes([2; 16]),
+        checksum: [3; 4],
+        content: b"content".to_vec(),
+        raw: Bytes::from_static(b"raw"),
+        chunks: vec![Box::from(&b"chunk"[..])],
+    };
+    let proto: proto::Object = object.clone().into();
+    assert_eq!(proto.id, vec![1; 16]);
+    assert_eq!(proto.owner, Bytes::from(vec![2; 16]));
+    assert_eq!(proto.checksum, Bytes::from(vec![3; 4]));
+    assert_eq!(proto.raw, b"raw".to_vec());
+    assert_eq!(proto.chunks, vec![Bytes::from_static(b"chunk")]);
+
+    let data: ObjectData = proto.clone().into();
+    assert_eq!(data.id, vec![1; 16]);
+    assert_eq!(data.content, b"content".to_vec());
+    assert_eq!(&*data.raw, b"raw");
+
+    let checked = CheckedObject::try_from(proto.clone()).unwrap();
+    assert_eq!(checked.id, object.id);
+    assert_eq!(checked.owner, object.owner);
+    assert_eq!(checked.checksum, object.checksum);
+    assert_eq!(checked.content, Bytes::from_static(b"content"));
+
+    let mut bad = proto;
+    bad.owner = Bytes::from_static(b"short");
+    let err = CheckedObject::try_from(bad).unwrap_err();
+    assert_eq!(
+        err.kind(),
+        &ErrorKind::LengthMismatch {
+            expected: 16,
+            actual: 5
+        }
+    );
+    assert_eq!(err.path(), "owner");
+}
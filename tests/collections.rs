@@ -0,0 +1,145 @@
+use std::collections::{BTreeSet, HashSet, VecDeque};
+
+use indexmap::{IndexMap, IndexSet};
+use prost_dto::{ErrorKind, FromProst, IntoProst, TryFromProst};
+use smallvec::SmallVec;
+
+mod proto {
+    use std::collections::HashMap;
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Child {
+        pub name: String,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Inventory {
+        pub tags: Vec<String>,
+        pub ids: Vec<u32>,
+        pub labels: Vec<String>,
+        pub by_name: HashMap<String, Child>,
+        pub weights: HashMap<String, u32>,
+        pub small: Vec<u32>,
+        pub queue: Vec<Child>,
+        pub stack: Vec<u32>,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, IntoProst, FromProst)]
+#[prost(target = "proto::Child")]
+pub struct Child {
+    pub name: String,
+}
+
+// A collection unknown to prost-dto.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stack<T>(Vec<T>);
+
+impl<T> FromIterator<T> for Stack<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl<T> IntoIterator for Stack<T> {
+    type IntoIter = std::vec::IntoIter<T>;
+    type Item = T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+fn double(v: u32) -> u32 {
+    v * 2
+}
+
+#[derive(Debug, Clone, PartialEq, IntoProst, FromProst)]
+#[prost(target = "proto::Inventory")]
+pub struct Inventory {
+    pub tags: BTreeSet<String>,
+    pub ids: HashSet<u32>,
+    // An empty repeated field is None.
+    pub labels: Option<IndexSet<String>>,
+    pub by_name: IndexMap<String, Child>,
+    pub weights: hashbrown::HashMap<String, u32>,
+    pub small: SmallVec<[u32; 4]>,
+    pub queue: VecDeque<Child>,
+    #[prost(collection)]
+    #[into_prost(map = "double")]
+    pub stack: Stack<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, TryFromProst)]
+#[prost(target = "proto::Inventory")]
+pub struct CheckedInventory {
+    #[from_prost(deny_duplicates)]
+    pub tags: BTreeSet<String>,
+    #[from_prost(deny_duplicates, try_map = "u8::try_from")]
+    pub ids: IndexSet<u8>,
+    pub labels: Option<BTreeSet<String>>,
+    pub by_name: IndexMap<String, Child>,
+    pub weights: hashbrown::HashMap<String, u32>,
+    pub small: SmallVec<[u32; 4]>,
+    pub queue: VecDeque<Child>,
+    #[prost(collection)]
+    pub stack: Stack<u32>,
+}
+
+fn main() {
+    let inventory = Inventory {
+        tags: BTreeSet::from(["b".to_owned(), "a".to_owned()]),
+        ids: HashSet::from([3, 1]),
+        labels: None,
+        by_name: IndexMap::from([(
+            "x".to_owned(),
+            Child {
+                name: "x".to_owned(),
+            },
+        )]),
+        weights: hashbrown::HashMap::from([("w".to_owned(), 7)]),
+        small: SmallVec::from_slice(&[1, 2, 3]),
+        queue: VecDeque::from([Child {
+            name: "q".to_owned(),
+        }]),
+        stack: Stack(vec![1, 2]),
+    };
+    let proto: proto::Inventory = inventory.clone().into();
+    assert_eq!(proto.tags, vec!["a".to_owned(), "b".to_owned()]);
+    let mut ids = proto.ids.clone();
+    ids.sort();
+    assert_eq!(ids, vec![1, 3]);
+    assert!(proto.labels.is_empty());
+    assert_eq!(proto.by_name["x"].name, "x");
+    assert_eq!(proto.weights["w"], 7);
+    assert_eq!(proto.small, vec![1, 2, 3]);
+    assert_eq!(proto.stack, vec![2, 4]);
+
+    let back: Inventory = proto.clone().into();
+    assert_eq!(back.stack, Stack(vec![2, 4]));
+    assert_eq!(
+        Inventory {
+            stack: inventory.stack.clone(),
+            ..back
+        },
+        inventory
+    );
+
+    let checked = CheckedInventory::try_from(proto.clone()).unwrap();
+    assert_eq!(checked.ids.len(), 2);
+    assert_eq!(checked.labels, None);
+
+    // Duplicates are dropped, unless the field denies them.
+    let mut duplicated = proto.clone();
+    duplicated.tags.push("a".to_owned());
+    let back: Inventory = duplicated.clone().into();
+    assert_eq!(back.tags.len(), 2);
+    let err = CheckedInventory::try_from(duplicated).unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::DuplicateValue);
+    assert_eq!(err.path(), "tags[2]");
+
+    let mut big = proto;
+    big.ids.push(300);
+    let err = CheckedInventory::try_from(big).unwrap_err();
+    assert_eq!(err.path(), "ids[2]");
+}
@@ -0,0 +1,75 @@
+use prost_dto::{FromProst, IntoProst, TryFromProst};
+
+mod sub {
+    #[repr(i32)]
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum Color {
+        Unknown = 0,
+        Red = 1,
+        Green = 2,
+        Blue = 3,
+    }
+
+    impl TryFrom<i32> for Color {
+        type Error = i32;
+
+        fn try_from(input: i32) -> Result<Self, i32> {
+            match input {
+                0 => Ok(Self::Unknown),
+                1 => Ok(Self::Red),
+                2 => Ok(Self::Green),
+                3 => Ok(Self::Blue),
+                _ => Err(input),
+            }
+        }
+    }
+}
+
+// `Blue` and the sentinel are not known to us, they are kept as raw values.
+#[derive(Debug, PartialEq, IntoProst, FromProst)]
+#[prost(target = "sub::Color")]
+pub enum Color {
+    Red,
+    Green,
+    #[prost(other)]
+    Unrecognized(i32),
+}
+
+#[derive(Debug, PartialEq, TryFromProst)]
+#[prost(target = "sub::Color")]
+pub enum CheckedColor {
+    Red,
+    Green,
+    #[prost(other)]
+    Unrecognized(i32),
+}
+
+fn main() {
+    assert_eq!(Color::try_from(1).unwrap(), Color::Red);
+    assert_eq!(Color::try_from(42).unwrap(), Color::Unrecognized(42));
+    assert_eq!(Color::try_from(0).unwrap(), Color::Unrecognized(0));
+    assert_eq!(Color::from(sub::Color::Blue), Color::Unrecognized(3));
+    assert_eq!(
+        CheckedColor::try_from(42).unwrap(),
+        CheckedColor::Unrecognized(42)
+    );
+    assert_eq!(
+        CheckedColor::try_from(sub::Color::Green).unwrap(),
+        CheckedColor::Green
+    );
+
+    // Round-trips through the wire value.
+    for value in [0, 1, 2, 3, 42] {
+        assert_eq!(i32::from(Color::try_from(value).unwrap()), value);
+    }
+
+    assert_eq!(
+        sub::Color::try_from(Color::Green).unwrap(),
+        sub::Color::Green
+    );
+    assert_eq!(
+        sub::Color::try_from(Color::Unrecognized(3)).unwrap(),
+        sub::Color::Blue
+    );
+    assert!(sub::Color::try_from(Color::Unrecognized(42)).is_err());
+}
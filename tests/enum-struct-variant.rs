@@ -0,0 +1,125 @@
+use prost_dto::{ErrorKind, FromProst, IntoProst, TryFromProst};
+
+mod sub {
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Point {
+        pub x: i32,
+        pub y: i32,
+    }
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Move {
+        pub to: Option<Point>,
+        pub speed: u32,
+    }
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Stop {
+        pub reason: String,
+    }
+    pub mod action {
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum Action {
+            Move(super::Move),
+            Stop(super::Stop),
+        }
+    }
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Action {
+        pub action: Option<action::Action>,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, IntoProst, FromProst)]
+#[prost(target = "sub::Point")]
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, IntoProst, FromProst)]
+#[prost(target = "sub::Action")]
+pub enum Action {
+    #[prost(target = "sub::Move")]
+    Move {
+        #[prost(required)]
+        to: Point,
+        #[prost(name = "speed")]
+        velocity: u32,
+        #[prost(skip)]
+        note: Option<String>,
+    },
+    #[prost(target = "sub::Stop")]
+    Stop { reason: String },
+}
+
+#[derive(Debug, Clone, PartialEq, IntoProst, TryFromProst)]
+#[prost(target = "sub::Action")]
+pub enum CheckedAction {
+    #[prost(target = "sub::Move")]
+    Move {
+        #[prost(required)]
+        to: Point,
+        speed: u32,
+    },
+    #[prost(target = "sub::Stop")]
+    Stop { reason: String },
+}
+
+fn main() {
+    let action = Action::Move {
+        to: Point { x: 1, y: 2 },
+        velocity: 10,
+        note: Some("ignored".to_string()),
+    };
+    let proto: sub::Action = action.into();
+    assert_eq!(
+        proto,
+        sub::Action {
+            action: Some(sub::action::Action::Move(sub::Move {
+                to: Some(sub::Point { x: 1, y: 2 }),
+                speed: 10,
+            })),
+        }
+    );
+
+    let action: Action = proto.clone().into();
+    assert_eq!(
+        action,
+        Action::Move {
+            to: Point { x: 1, y: 2 },
+            velocity: 10,
+            note: None,
+        }
+    );
+
+    let action: CheckedAction = proto.try_into().unwrap();
+    assert_eq!(
+        action,
+        CheckedAction::Move {
+            to: Point { x: 1, y: 2 },
+            speed: 10,
+        }
+    );
+
+    let stop = sub::Action {
+        action: Some(sub::action::Action::Stop(sub::Stop {
+            reason: "done".to_string(),
+        })),
+    };
+    let action: Action = stop.into();
+    assert_eq!(
+        action,
+        Action::Stop {
+            reason: "done".to_string()
+        }
+    );
+
+    let proto = sub::Action {
+        action: Some(sub::action::Action::Move(sub::Move {
+            to: None,
+            speed: 10,
+        })),
+    };
+    let err = CheckedAction::try_from(proto).unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::MissingRequired);
+    assert_eq!(err.path(), "move.to");
+}
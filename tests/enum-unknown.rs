@@ -0,0 +1,144 @@
+use prost_dto::{ErrorKind, FromProst, IntoProst, ProstEnumeration, TryFromProst};
+
+mod sub {
+    #[repr(i32)]
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum HttpMethod {
+        Unspecified = 0,
+        Get = 1,
+        Post = 2,
+    }
+
+    impl TryFrom<i32> for HttpMethod {
+        type Error = i32;
+
+        fn try_from(input: i32) -> Result<Self, i32> {
+            match input {
+                0 => Ok(Self::Unspecified),
+                1 => Ok(Self::Get),
+                2 => Ok(Self::Post),
+                _ => Err(input),
+            }
+        }
+    }
+
+    #[repr(i32)]
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum Level {
+        Low = 0,
+        High = 1,
+    }
+
+    impl TryFrom<i32> for Level {
+        type Error = i32;
+
+        fn try_from(input: i32) -> Result<Self, i32> {
+            match input {
+                0 => Ok(Self::Low),
+                1 => Ok(Self::High),
+                _ => Err(input),
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Route {
+        pub method: i32,
+        pub any_method: Option<i32>,
+        pub level: i32,
+    }
+}
+
+#[derive(Debug, PartialEq, IntoProst, TryFromProst)]
+#[prost(target = "sub::HttpMethod", unknown = "Unspecified")]
+pub enum HttpMethod {
+    Get,
+    Post,
+}
+
+// The sentinel is mapped to one of our variants.
+#[derive(Debug, PartialEq, IntoProst, FromProst)]
+#[prost(target = "sub::HttpMethod", unknown = "Unspecified")]
+pub enum AnyMethod {
+    #[prost(name = "Unspecified")]
+    Any,
+    Get,
+    Post,
+}
+
+// Every value of the enumeration is meaningful.
+#[derive(Debug, PartialEq, IntoProst, FromProst)]
+#[prost(target = "sub::Level", no_unknown)]
+pub enum Level {
+    Low,
+    High,
+}
+
+// The sentinel of optional enumeration fields is `None`.
+#[derive(Debug, PartialEq, TryFromProst)]
+#[prost(target = "sub::Route")]
+pub struct Route {
+    #[prost(enumeration = "sub::HttpMethod")]
+    pub method: Option<HttpMethod>,
+    #[prost(enumeration = "sub::HttpMethod")]
+    pub any_method: Option<AnyMethod>,
+    #[prost(enumeration = "sub::Level")]
+    pub level: Option<Level>,
+}
+
+fn main() {
+    assert_eq!(HttpMethod::try_from(1).unwrap(), HttpMethod::Get);
+    let err = HttpMethod::try_from(0).unwrap_err();
+    assert_eq!(
+        err.kind(),
+        &ErrorKind::UnknownEnumValue {
+            enum_name: "sub::HttpMethod",
+            value: 0
+        }
+    );
+    assert_eq!(HttpMethod::try_from_i32_or_none(0).unwrap(), None);
+    assert_eq!(
+        HttpMethod::try_from_i32_or_none(2).unwrap(),
+        Some(HttpMethod::Post)
+    );
+    assert!(HttpMethod::try_from_i32_or_none(9).is_err());
+
+    assert_eq!(
+        AnyMethod::from(sub::HttpMethod::Unspecified),
+        AnyMethod::Any
+    );
+    assert_eq!(AnyMethod::try_from(0).unwrap(), AnyMethod::Any);
+    assert_eq!(
+        sub::HttpMethod::from(AnyMethod::Any),
+        sub::HttpMethod::Unspecified
+    );
+    assert_eq!(AnyMethod::try_from_i32_or_none(0).unwrap(), None);
+
+    assert_eq!(Level::from(sub::Level::Low), Level::Low);
+    assert_eq!(Level::try_from_i32_or_none(0).unwrap(), Some(Level::Low));
+    assert_eq!(i32::from(Level::High), 1);
+
+    let route = Route::try_from(sub::Route {
+        method: 0,
+        any_method: Some(0),
+        level: 0,
+    })
+    .unwrap();
+    assert_eq!(
+        route,
+        Route {
+            method: None,
+            any_method: None,
+            level: Some(Level::Low),
+        }
+    );
+    let route = Route::try_from(sub::Route {
+        method: 1,
+        any_method: None,
+        level: 1,
+    })
+    .unwrap();
+    assert_eq!(route.method, Some(HttpMethod::Get));
+    assert_eq!(route.any_method, None);
+    assert_eq!(route.level, Some(Level::High));
+}
@@ -0,0 +1,161 @@
+use prost_dto::{ErrorKind, FromProst, IntoProst, TryFromProst};
+
+mod sub {
+    #[repr(i32)]
+    #[derive(Debug, Clone, Copy, Default, PartialEq)]
+    pub enum HttpMethod {
+        #[default]
+        Unknown = 0,
+        Get = 1,
+        Post = 2,
+    }
+
+    impl TryFrom<i32> for HttpMethod {
+        type Error = i32;
+
+        fn try_from(input: i32) -> Result<Self, i32> {
+            match input {
+                0 => Ok(Self::Unknown),
+                1 => Ok(Self::Get),
+                2 => Ok(Self::Post),
+                _ => Err(input),
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Request {
+        pub method: i32,
+        pub fallback: i32,
+        pub preferred: Option<i32>,
+        pub allowed: Vec<i32>,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, IntoProst, FromProst)]
+#[prost(target = "sub::HttpMethod")]
+pub enum HttpMethod {
+    Get,
+    Post,
+    #[prost(other)]
+    Unrecognized(i32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, TryFromProst)]
+#[prost(target = "sub::HttpMethod")]
+pub enum CheckedHttpMethod {
+    Get,
+    Post,
+}
+
+#[derive(Debug, Clone, PartialEq, IntoProst)]
+#[prost(target = "sub::Request")]
+pub struct Request {
+    #[prost(enumeration = "sub::HttpMethod")]
+    pub method: HttpMethod,
+    #[prost(enumeration = "sub::HttpMethod")]
+    pub fallback: Option<HttpMethod>,
+    #[prost(enumeration = "sub::HttpMethod")]
+    pub preferred: Option<HttpMethod>,
+    #[prost(enumeration = "sub::HttpMethod")]
+    pub allowed: Vec<HttpMethod>,
+}
+
+// Unknown values are kept by an `other` variant.
+#[derive(Debug, Clone, PartialEq, TryFromProst)]
+#[prost(target = "sub::Request")]
+pub struct RequestPreferences {
+    #[prost(enumeration = "sub::HttpMethod")]
+    pub fallback: Option<HttpMethod>,
+    #[prost(enumeration = "sub::HttpMethod")]
+    pub preferred: Option<CheckedHttpMethod>,
+}
+
+#[derive(Debug, Clone, PartialEq, TryFromProst)]
+#[prost(target = "sub::Request")]
+pub struct CheckedRequest {
+    #[prost(enumeration = "sub::HttpMethod")]
+    pub method: CheckedHttpMethod,
+    #[prost(enumeration = "sub::HttpMethod")]
+    pub fallback: Option<CheckedHttpMethod>,
+    #[prost(enumeration = "sub::HttpMethod")]
+    pub allowed: Vec<CheckedHttpMethod>,
+}
+
+fn main() {
+    let request = Request {
+        method: HttpMethod::Get,
+        fallback: None,
+        preferred: Some(HttpMethod::Post),
+        allowed: vec![HttpMethod::Get, HttpMethod::Post],
+    };
+    let proto: sub::Request = request.clone().into();
+    assert_eq!(
+        proto,
+        sub::Request {
+            method: 1,
+            fallback: 0,
+            preferred: Some(2),
+            allowed: vec![1, 2],
+        }
+    );
+    assert_eq!(
+        RequestPreferences::try_from(proto.clone()).unwrap(),
+        RequestPreferences {
+            fallback: None,
+            preferred: Some(CheckedHttpMethod::Post),
+        }
+    );
+    let checked = CheckedRequest::try_from(proto).unwrap();
+    assert_eq!(checked.method, CheckedHttpMethod::Get);
+    assert_eq!(checked.fallback, None);
+
+    // Unknown values
+    let proto = sub::Request {
+        method: 2,
+        fallback: 7,
+        preferred: Some(9),
+        allowed: vec![1],
+    };
+    // are kept by the `other` variant, or rejected.
+    let err = RequestPreferences::try_from(proto.clone()).unwrap_err();
+    assert_eq!(
+        err.kind(),
+        &ErrorKind::UnknownEnumValue {
+            enum_name: "sub::HttpMethod",
+            value: 9
+        }
+    );
+    assert_eq!(err.path(), "preferred");
+    let preferences = RequestPreferences::try_from(sub::Request {
+        preferred: None,
+        ..proto.clone()
+    })
+    .unwrap();
+    assert_eq!(preferences.fallback, Some(HttpMethod::Unrecognized(7)));
+    let err = CheckedRequest::try_from(proto).unwrap_err();
+    assert_eq!(
+        err.kind(),
+        &ErrorKind::UnknownEnumValue {
+            enum_name: "sub::HttpMethod",
+            value: 7
+        }
+    );
+    assert_eq!(err.path(), "fallback");
+
+    let proto = sub::Request {
+        method: 2,
+        fallback: 1,
+        preferred: None,
+        allowed: vec![1, 7],
+    };
+    let err = CheckedRequest::try_from(proto).unwrap_err();
+    assert_eq!(
+        err.kind(),
+        &ErrorKind::UnknownEnumValue {
+            enum_name: "sub::HttpMethod",
+            value: 7
+        }
+    );
+    assert_eq!(err.path(), "allowed[1]");
+}
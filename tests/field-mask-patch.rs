@@ -0,0 +1,119 @@
+use prost_dto::{ErrorKind, ProstPatch};
+use prost_types::FieldMask;
+
+mod proto {
+    #[derive(Debug, Clone, Default, PartialEq)]
+    pub struct Address {
+        pub street: String,
+        pub city: String,
+    }
+
+    #[derive(Debug, Clone, Default, PartialEq)]
+    pub struct User {
+        pub name: String,
+        pub mail: String,
+        pub age: u32,
+        pub address: Option<Address>,
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, ProstPatch)]
+#[prost(target = "proto::Address")]
+pub struct Address {
+    pub street: String,
+    pub city: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, ProstPatch)]
+#[prost(target = "proto::User")]
+pub struct User {
+    pub name: String,
+    #[prost(name = "mail")]
+    pub email: String,
+    #[from_prost(try_map = "u8::try_from")]
+    pub age: u8,
+    #[prost(patch)]
+    pub address: Address,
+    #[prost(skip)]
+    pub version: u64,
+}
+
+fn mask(paths: &[&str]) -> FieldMask {
+    FieldMask {
+        paths: paths.iter().map(|p| p.to_string()).collect(),
+    }
+}
+
+fn main() {
+    assert_eq!(User::MASK_PATHS, &["name", "mail", "age", "address"]);
+    assert!(User::is_valid_mask_path("address.city"));
+    assert!(!User::is_valid_mask_path("address.zip"));
+    assert!(!User::is_valid_mask_path("name.first"));
+    assert!(!User::is_valid_mask_path("email"));
+
+    let original = User {
+        name: "Ada".to_owned(),
+        email: "ada@old.example".to_owned(),
+        age: 36,
+        address: Address {
+            street: "1 Main St".to_owned(),
+            city: "London".to_owned(),
+        },
+        version: 7,
+    };
+    let proto = proto::User {
+        name: "Grace".to_owned(),
+        mail: "ada@new.example".to_owned(),
+        age: 1000,
+        address: Some(proto::Address {
+            street: "2 High St".to_owned(),
+            city: "Paris".to_owned(),
+        }),
+    };
+
+    // Only masked paths are copied, renamed fields use the proto name.
+    let mut user = original.clone();
+    user.apply_proto_with_mask(proto.clone(), &mask(&["mail", "address.city"]))
+        .unwrap();
+    assert_eq!(user.name, "Ada");
+    assert_eq!(user.email, "ada@new.example");
+    assert_eq!(user.address.street, "1 Main St");
+    assert_eq!(user.address.city, "Paris");
+    assert_eq!(user.version, 7);
+
+    // The whole nested message.
+    let mut user = original.clone();
+    user.apply_proto_with_mask(proto.clone(), &mask(&["address"]))
+        .unwrap();
+    assert_eq!(user.address.street, "2 High St");
+
+    // A missing nested message clears the masked fields.
+    let mut user = original.clone();
+    let mut cleared = proto.clone();
+    cleared.address = None;
+    user.apply_proto_with_mask(cleared, &mask(&["address.street"]))
+        .unwrap();
+    assert_eq!(user.address.street, "");
+    assert_eq!(user.address.city, "London");
+
+    // Unknown paths are rejected before anything is applied.
+    let mut user = original.clone();
+    let err = user
+        .apply_proto_with_mask(proto.clone(), &mask(&["name", "email"]))
+        .unwrap_err();
+    assert_eq!(
+        err.kind(),
+        &ErrorKind::UnknownMaskPath {
+            path: "email".to_owned()
+        }
+    );
+    assert_eq!(user, original);
+
+    // Conversion errors carry the field path.
+    let mut user = original;
+    let err = user
+        .apply_proto_with_mask(proto, &mask(&["age"]))
+        .unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::Mapper);
+    assert_eq!(err.path(), "age");
+}
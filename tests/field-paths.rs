@@ -0,0 +1,67 @@
+use prost_dto::{FieldMaskBuilder, IntoProst, ProstFields};
+
+mod proto {
+    pub struct Schedule {
+        pub run_at: u64,
+        pub timezone: String,
+    }
+
+    pub struct Job {
+        pub id: u64,
+        pub schedule: Option<Schedule>,
+    }
+}
+
+#[derive(ProstFields, IntoProst)]
+#[prost(target = "proto::Schedule")]
+pub struct Schedule {
+    #[prost(name = "run_at")]
+    pub start: u64,
+    pub timezone: String,
+}
+
+#[derive(ProstFields, IntoProst)]
+#[prost(target = "proto::Job")]
+pub struct Job {
+    pub id: u64,
+    pub schedule: Option<Schedule>,
+    #[prost(skip)]
+    pub attempts: u32,
+}
+
+#[derive(ProstFields)]
+#[prost(target = "proto::Job")]
+pub struct JobId(#[prost(name = "id")] pub u64);
+
+fn main() {
+    assert_eq!(Job::PROTO_FIELDS, &["id", "schedule"]);
+    assert_eq!(Schedule::PROTO_FIELDS, &["run_at", "timezone"]);
+    assert_eq!(JobId::PROTO_FIELDS, &["id"]);
+
+    let paths = Job::field_paths();
+    assert_eq!(paths.id().as_str(), "id");
+    assert_eq!(
+        paths.schedule().nested::<Schedule>().start().to_string(),
+        "schedule.run_at"
+    );
+    assert_eq!(JobId::field_paths().id().as_str(), "id");
+
+    let mask = FieldMaskBuilder::new()
+        .path(paths.id())
+        .path(paths.schedule().nested::<Schedule>().timezone());
+    assert_eq!(mask.paths(), &["id", "schedule.timezone"]);
+
+    // The paths match the fields of the converted message.
+    let proto: proto::Job = Job {
+        id: 1,
+        schedule: Some(Schedule {
+            start: 10,
+            timezone: "UTC".to_owned(),
+        }),
+        attempts: 3,
+    }
+    .into();
+    let schedule = proto.schedule.unwrap();
+    assert_eq!((proto.id, schedule.run_at), (1, 10));
+    assert_eq!(schedule.timezone, "UTC");
+}
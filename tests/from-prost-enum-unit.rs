@@ -1,7 +1,8 @@
-use prost_dto::FromProst;
+use prost_dto::{ErrorKind, FromProst};
 
 mod sub {
     #[repr(i32)]
+    #[derive(Clone, Copy)]
     pub enum HttpMethod {
         Unknown = 0,
         Get = 1,
@@ -9,20 +10,41 @@ mod sub {
         Put = 3,
     }
 
-    impl HttpMethod {
+    impl TryFrom<i32> for HttpMethod {
+        type Error = i32;
+
+        fn try_from(input: i32) -> Result<Self, i32> {
+            match input {
+                0 => Ok(Self::Unknown),
+                1 => Ok(Self::Get),
+                2 => Ok(Self::Post),
+                3 => Ok(Self::Put),
+                _ => Err(input),
+            }
+        }
+    }
+
+    // Generated by older prost versions.
+    #[repr(i32)]
+    #[derive(Clone, Copy)]
+    pub enum Color {
+        Unknown = 0,
+        Red = 1,
+    }
+
+    impl Color {
+        #[deprecated]
         pub fn from_i32(input: i32) -> Option<Self> {
             match input {
                 0 => Some(Self::Unknown),
-                1 => Some(Self::Get),
-                2 => Some(Self::Post),
-                3 => Some(Self::Put),
+                1 => Some(Self::Red),
                 _ => None,
             }
         }
     }
 }
 
-#[derive(FromProst)]
+#[derive(Debug, PartialEq, FromProst)]
 #[prost(target = "sub::HttpMethod")]
 pub enum HttpMethod {
     Get,
@@ -30,4 +52,36 @@ pub enum HttpMethod {
     Put,
 }
 
-fn main() {}
+#[derive(Debug, PartialEq, FromProst)]
+#[prost(target = "sub::Color")]
+#[from_prost(use_from_i32)]
+pub enum Color {
+    Red,
+}
+
+fn main() {
+    assert_eq!(HttpMethod::from(sub::HttpMethod::Post), HttpMethod::Post);
+    assert_eq!(HttpMethod::try_from(3).unwrap(), HttpMethod::Put);
+    let err = HttpMethod::try_from(42).unwrap_err();
+    assert_eq!(
+        err.kind(),
+        &ErrorKind::UnknownEnumValue {
+            enum_name: "sub::HttpMethod",
+            value: 42
+        }
+    );
+
+    // The sentinel is rejected rather than converted.
+    let err = HttpMethod::try_from(0).unwrap_err();
+    assert_eq!(
+        err.kind(),
+        &ErrorKind::UnknownEnumValue {
+            enum_name: "sub::HttpMethod",
+            value: 0
+        }
+    );
+
+    assert_eq!(Color::from(sub::Color::Red), Color::Red);
+    assert_eq!(Color::try_from(1).unwrap(), Color::Red);
+    assert!(Color::try_from(7).is_err());
+}
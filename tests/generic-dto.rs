@@ -0,0 +1,96 @@
+use std::fmt::Debug;
+
+use prost_dto::{FromProst, IntoProst, TryFromProst};
+
+mod sub {
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Item {
+        pub name: String,
+    }
+    pub struct Page {
+        pub items: Vec<Item>,
+        pub next_page_token: Option<String>,
+    }
+    pub struct Label {
+        pub key: String,
+        pub value: String,
+    }
+    pub mod envelope {
+        pub enum Envelope {
+            Item(super::Item),
+        }
+    }
+    pub struct Envelope {
+        pub envelope: Option<envelope::Envelope>,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, IntoProst, FromProst)]
+#[prost(target = "sub::Item")]
+pub struct Item {
+    pub name: String,
+}
+
+#[derive(Debug, PartialEq, IntoProst, FromProst)]
+#[prost(target = "sub::Page")]
+#[into_prost(bound = "T: Into<sub::Item>")]
+#[from_prost(bound = "T: From<sub::Item>")]
+pub struct Page<T>
+where
+    T: Debug,
+{
+    pub items: Vec<T>,
+    pub next_page_token: Option<String>,
+}
+
+// Lifetimes are threaded through as well.
+#[derive(IntoProst)]
+#[prost(target = "sub::Label")]
+pub struct Label<'a> {
+    pub key: &'a str,
+    pub value: &'a str,
+}
+
+#[derive(Debug, PartialEq, IntoProst, TryFromProst)]
+#[prost(
+    target = "sub::Envelope",
+    bound = "T: Into<sub::Item> + TryFrom<sub::Item>"
+)]
+#[from_prost(bound = "T: TryFrom<sub::Item>, prost_dto::ConversionError: From<T::Error>")]
+pub enum Envelope<T> {
+    Item(T),
+}
+
+fn main() {
+    let page = Page {
+        items: vec![Item {
+            name: "foo".to_owned(),
+        }],
+        next_page_token: Some("next".to_owned()),
+    };
+    let proto: sub::Page = page.into();
+    assert_eq!(
+        proto.items,
+        vec![sub::Item {
+            name: "foo".to_owned()
+        }]
+    );
+    let page: Page<Item> = proto.into();
+    assert_eq!(page.items[0].name, "foo");
+
+    let label: sub::Label = Label {
+        key: "k",
+        value: "v",
+    }
+    .into();
+    assert_eq!((label.key.as_str(), label.value.as_str()), ("k", "v"));
+
+    let envelope: sub::Envelope = Envelope::Item(page.items[0].clone()).into();
+    let envelope: Envelope<Item> = envelope.try_into().unwrap();
+    assert_eq!(
+        envelope,
+        Envelope::Item(Item {
+            name: "foo".to_owned()
+        })
+    );
+}
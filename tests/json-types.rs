@@ -0,0 +1,131 @@
+use std::collections::BTreeMap;
+
+use prost_dto::{ErrorKind, IntoProst, TryFromProst};
+use prost_types::value::Kind;
+use serde_json::json;
+
+mod sub {
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Config {
+        pub settings: Option<prost_types::Struct>,
+        pub default: Option<prost_types::Value>,
+        pub overrides: Vec<prost_types::Value>,
+        pub tags: Option<prost_types::ListValue>,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, IntoProst)]
+#[prost(target = "sub::Config")]
+pub struct Config {
+    #[prost(required)]
+    pub settings: serde_json::Map<String, serde_json::Value>,
+    pub default: Option<serde_json::Value>,
+    pub overrides: Vec<serde_json::Value>,
+    // A `google.protobuf.ListValue` rather than a repeated `Value`.
+    #[prost(well_known)]
+    pub tags: Option<Vec<serde_json::Value>>,
+}
+
+#[derive(Debug, Clone, PartialEq, TryFromProst)]
+#[prost(target = "sub::Config")]
+pub struct CheckedConfig {
+    #[prost(required)]
+    pub settings: serde_json::Map<String, serde_json::Value>,
+    pub default: Option<serde_json::Value>,
+    pub overrides: Vec<serde_json::Value>,
+    // A `google.protobuf.ListValue` rather than a repeated `Value`.
+    #[prost(well_known)]
+    pub tags: Option<Vec<serde_json::Value>>,
+}
+
+fn number(n: f64) -> prost_types::Value {
+    prost_types::Value {
+        kind: Some(Kind::NumberValue(n)),
+    }
+}
+
+fn main() {
+    let settings = json!({
+        "name": "svc",
+        "replicas": 3,
+        "ratio": 0.5,
+        "tags": ["a", null, true],
+        "nested": { "empty": {} },
+    });
+    let config = Config {
+        settings: settings.as_object().unwrap().clone(),
+        default: Some(serde_json::Value::Null),
+        overrides: vec![json!(1), json!("x")],
+        tags: Some(vec![
+            json!("a"),
+            serde_json::Value::Null,
+            json!(9_007_199_254_740_993_i64),
+        ]),
+    };
+    let proto: sub::Config = config.clone().into();
+    let fields = &proto.settings.as_ref().unwrap().fields;
+    assert_eq!(fields["replicas"], number(3.0));
+    assert_eq!(fields["ratio"], number(0.5));
+    assert_eq!(
+        proto.default,
+        Some(prost_types::Value {
+            kind: Some(Kind::NullValue(0)),
+        })
+    );
+    assert_eq!(proto.overrides[0], number(1.0));
+    // Integers beyond 2^53 lose precision as f64.
+    let tags = &proto.tags.as_ref().unwrap().values;
+    assert_eq!(
+        tags[1],
+        prost_types::Value {
+            kind: Some(Kind::NullValue(0)),
+        }
+    );
+    assert_eq!(tags[2], number(9_007_199_254_740_992.0));
+
+    // Integral numbers come back as JSON integers.
+    let checked = CheckedConfig::try_from(proto.clone()).unwrap();
+    assert_eq!(checked.settings, config.settings);
+    assert_eq!(checked.settings["replicas"], json!(3));
+    assert_eq!(checked.default, config.default);
+    assert_eq!(checked.overrides, config.overrides);
+    // And aren't converted back to JSON integers.
+    assert_eq!(
+        checked.tags,
+        Some(vec![
+            json!("a"),
+            serde_json::Value::Null,
+            json!(9_007_199_254_740_992.0)
+        ])
+    );
+
+    // An unset kind is null.
+    let mut unset = proto.clone();
+    unset.default = Some(prost_types::Value { kind: None });
+    assert_eq!(
+        CheckedConfig::try_from(unset).unwrap().default,
+        Some(serde_json::Value::Null)
+    );
+
+    // NaN can't be represented in JSON.
+    let mut nan = proto.clone();
+    nan.settings = Some(prost_types::Struct {
+        fields: BTreeMap::from([("ratio".to_owned(), number(f64::NAN))]),
+    });
+    let err = CheckedConfig::try_from(nan).unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::OutOfRange { .. }));
+    assert_eq!(err.path(), "settings[\"ratio\"]");
+
+    let mut nan = proto.clone();
+    nan.tags = Some(prost_types::ListValue {
+        values: vec![number(1.0), number(f64::NAN)],
+    });
+    let err = CheckedConfig::try_from(nan).unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::OutOfRange { .. }));
+    assert_eq!(err.path(), "tags[1]");
+
+    let mut missing = proto;
+    missing.settings = None;
+    let err = CheckedConfig::try_from(missing).unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::MissingRequired));
+}
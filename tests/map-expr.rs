@@ -0,0 +1,128 @@
+use prost_dto::{ErrorKind, FromProst, IntoProst, ProstDto, TryFromProst};
+
+mod proto {
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Sample {
+        pub celsius: f64,
+        pub readings: Vec<f64>,
+        pub peak: Option<f64>,
+        pub label: String,
+        pub ids: Vec<String>,
+        pub parent: Option<String>,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Tag {
+        pub label: String,
+    }
+}
+
+// Generic over the value, so the same module converts `T`, `Option<T>` and
+// `Vec<T>` fields (the mapper is applied to each value).
+mod text {
+    use std::fmt::Display;
+    use std::str::FromStr;
+
+    pub fn into_prost<T: Display>(value: T) -> String {
+        value.to_string()
+    }
+
+    pub fn from_prost<T: FromStr + Default>(value: String) -> T {
+        value.parse().unwrap_or_default()
+    }
+}
+
+// Rejects values that aren't numbers, only `from_prost` is needed by
+// TryFromProst.
+mod strict_text {
+    use std::num::ParseIntError;
+
+    pub fn from_prost(value: String) -> Result<u64, ParseIntError> {
+        value.parse()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, ProstDto)]
+#[prost(target = "proto::Sample")]
+pub struct Sample {
+    #[into_prost(map_expr = "|f: f64| (f - 32.0) / 1.8")]
+    #[from_prost(map_expr = "|c: f64| c * 1.8 + 32.0")]
+    #[prost(name = "celsius")]
+    pub fahrenheit: f64,
+    #[into_prost(map_expr = "|v: f64| v / 10.0")]
+    #[from_prost(map_expr = "|v: f64| v * 10.0")]
+    pub readings: Vec<f64>,
+    #[into_prost(map_expr = "f64::from")]
+    #[from_prost(map_expr = "|v: f64| v as f32")]
+    pub peak: Option<f32>,
+    #[prost(with = "text")]
+    pub label: u32,
+    #[prost(with = "text")]
+    pub ids: Vec<u64>,
+    #[prost(with = "text")]
+    pub parent: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, IntoProst, FromProst)]
+#[prost(target = "proto::Tag")]
+pub struct Tag {
+    #[into_prost(map_expr = "str::to_uppercase", map_by_ref)]
+    #[from_prost(map_expr = "|v: String| v.to_lowercase()")]
+    pub label: String,
+}
+
+#[derive(Debug, Clone, PartialEq, TryFromProst)]
+#[prost(target = "proto::Sample")]
+pub struct CheckedSample {
+    #[prost(name = "celsius")]
+    #[from_prost(map_expr = "|c: f64| c * 1.8 + 32.0")]
+    pub fahrenheit: f64,
+    #[prost(skip)]
+    pub readings: Vec<f64>,
+    #[prost(skip)]
+    pub peak: Option<f32>,
+    #[prost(with = "text")]
+    pub label: u32,
+    #[prost(try_with = "strict_text")]
+    pub ids: Vec<u64>,
+    #[prost(try_with = "strict_text")]
+    pub parent: Option<u64>,
+}
+
+fn main() {
+    let sample = Sample {
+        fahrenheit: 212.0,
+        readings: vec![10.0, 20.0],
+        peak: Some(1.5),
+        label: 7,
+        ids: vec![1, 2],
+        parent: Some(3),
+    };
+    let proto: proto::Sample = sample.clone().into();
+    assert_eq!(proto.celsius, 100.0);
+    assert_eq!(proto.readings, vec![1.0, 2.0]);
+    assert_eq!(proto.peak, Some(1.5));
+    assert_eq!(proto.label, "7");
+    assert_eq!(proto.ids, vec!["1", "2"]);
+    assert_eq!(proto.parent.as_deref(), Some("3"));
+
+    let back: Sample = proto.clone().into();
+    assert_eq!(back, sample);
+
+    let checked = CheckedSample::try_from(proto.clone()).unwrap();
+    assert_eq!((checked.fahrenheit, checked.label), (212.0, 7));
+    assert_eq!((checked.ids, checked.parent), (vec![1, 2], Some(3)));
+
+    let mut invalid = proto.clone();
+    invalid.ids.push("x".to_owned());
+    let err = CheckedSample::try_from(invalid).unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::Mapper);
+    assert_eq!(err.path(), "ids[2]");
+
+    let tag: proto::Tag = Tag {
+        label: "Abc".to_owned(),
+    }
+    .into();
+    assert_eq!(tag.label, "ABC");
+    assert_eq!(Tag::from(tag).label, "abc");
+}
@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+
+use prost_dto::{FromProst, IntoProst, TryFromProst};
+
+mod proto {
+    use std::collections::HashMap;
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct User {
+        pub name: String,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Directory {
+        pub users: HashMap<String, User>,
+        pub quotas: HashMap<i32, u64>,
+        pub labels: HashMap<String, String>,
+        pub members: HashMap<String, User>,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct UserId(String);
+
+impl UserId {
+    fn into_string(self) -> String {
+        self.0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Tier {
+    Free,
+    Pro,
+}
+
+fn tier_to_i32(tier: Tier) -> i32 {
+    tier as i32
+}
+
+fn tier_from_i32(value: i32) -> Tier {
+    try_tier(value).unwrap_or(Tier::Free)
+}
+
+fn try_tier(value: i32) -> Result<Tier, String> {
+    match value {
+        0 => Ok(Tier::Free),
+        1 => Ok(Tier::Pro),
+        _ => Err(format!("unknown tier {value}")),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, IntoProst, FromProst)]
+#[prost(target = "proto::User")]
+pub struct User {
+    pub name: String,
+}
+
+// The entry of a map converted by `map_entries`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Member {
+    pub key: UserId,
+    pub value: User,
+}
+
+#[derive(Debug, Clone, PartialEq, IntoProst, FromProst)]
+#[prost(target = "proto::Directory")]
+pub struct Directory {
+    #[into_prost(map_key = "UserId::into_string")]
+    #[from_prost(map_key = "UserId")]
+    pub users: HashMap<UserId, User>,
+    #[into_prost(map_key = "tier_to_i32")]
+    #[from_prost(map_key = "tier_from_i32")]
+    pub quotas: HashMap<Tier, u64>,
+    #[prost(map_entries)]
+    pub labels: Vec<(String, String)>,
+    #[prost(map_entries)]
+    #[into_prost(map_key = "UserId::into_string")]
+    #[from_prost(map_key = "UserId")]
+    pub members: Vec<Member>,
+}
+
+#[derive(Debug, Clone, PartialEq, TryFromProst)]
+#[prost(target = "proto::Directory")]
+pub struct CheckedDirectory {
+    #[from_prost(map_key = "UserId")]
+    pub users: HashMap<UserId, User>,
+    #[from_prost(try_map_key = "try_tier")]
+    pub quotas: HashMap<Tier, u64>,
+    #[prost(map_entries)]
+    pub labels: Vec<(String, String)>,
+    #[prost(map_entries)]
+    #[from_prost(map_key = "UserId")]
+    pub members: Vec<Member>,
+}
+
+fn main() {
+    let alice = UserId("alice".to_owned());
+    let directory = Directory {
+        users: HashMap::from([(
+            alice.clone(),
+            User {
+                name: "Alice".to_owned(),
+            },
+        )]),
+        quotas: HashMap::from([(Tier::Free, 10), (Tier::Pro, 100)]),
+        labels: vec![("team".to_owned(), "core".to_owned())],
+        members: vec![Member {
+            key: alice.clone(),
+            value: User {
+                name: "Alice".to_owned(),
+            },
+        }],
+    };
+    let proto: proto::Directory = directory.clone().into();
+    assert_eq!(proto.users["alice"].name, "Alice");
+    assert_eq!(proto.quotas[&1], 100);
+    assert_eq!(proto.labels["team"], "core");
+    assert_eq!(proto.members["alice"].name, "Alice");
+
+    let back: Directory = proto.clone().into();
+    assert_eq!(back, directory);
+
+    let checked = CheckedDirectory::try_from(proto.clone()).unwrap();
+    assert_eq!(checked.quotas[&Tier::Pro], 100);
+    assert_eq!(checked.members[0].key, alice);
+
+    let mut unknown = proto;
+    unknown.quotas.insert(7, 1);
+    let err = CheckedDirectory::try_from(unknown).unwrap_err();
+    assert_eq!(err.path(), "quotas[7]");
+}
@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+
+use prost_dto::{FromProst, IntoProst, TryFromProst};
+
+mod proto {
+    use std::collections::HashMap;
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Child {
+        pub name: String,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Tree {
+        pub children: Vec<Child>,
+        pub slots: Vec<Option<Child>>,
+        pub by_name: HashMap<String, Child>,
+        pub grid: Vec<Vec<u32>>,
+        pub scores: Vec<u32>,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, IntoProst, FromProst)]
+#[prost(target = "proto::Child")]
+pub struct Child {
+    pub name: String,
+}
+
+fn double(v: u32) -> u32 {
+    v * 2
+}
+
+fn half(v: u32) -> u32 {
+    v / 2
+}
+
+#[derive(Debug, Clone, PartialEq, IntoProst, FromProst)]
+#[prost(target = "proto::Tree")]
+pub struct Tree {
+    // An empty repeated field is None.
+    pub children: Option<Vec<Child>>,
+    pub slots: Vec<Option<Child>>,
+    pub by_name: Option<HashMap<String, Child>>,
+    // The mapper applies to each value.
+    #[into_prost(map = "double")]
+    #[from_prost(map = "half")]
+    pub grid: Vec<Vec<u32>>,
+    #[into_prost(map = "double")]
+    #[from_prost(map = "half")]
+    pub scores: Option<Vec<u32>>,
+}
+
+#[derive(Debug, Clone, PartialEq, TryFromProst)]
+#[prost(target = "proto::Tree")]
+pub struct CheckedTree {
+    pub children: Option<Vec<Child>>,
+    pub slots: Vec<Option<Child>>,
+    pub by_name: Option<HashMap<String, Child>>,
+    #[from_prost(try_map = "u8::try_from")]
+    pub grid: Vec<Vec<u8>>,
+    pub scores: Option<Vec<u32>>,
+}
+
+fn main() {
+    let tree = Tree {
+        children: Some(vec![Child {
+            name: "a".to_owned(),
+        }]),
+        slots: vec![
+            None,
+            Some(Child {
+                name: "b".to_owned(),
+            }),
+        ],
+        by_name: None,
+        grid: vec![vec![1, 2], vec![3]],
+        scores: None,
+    };
+    let proto: proto::Tree = tree.clone().into();
+    assert_eq!(proto.children.len(), 1);
+    assert_eq!(proto.slots[0], None);
+    assert!(proto.by_name.is_empty());
+    assert_eq!(proto.grid, vec![vec![2, 4], vec![6]]);
+    assert!(proto.scores.is_empty());
+
+    let back: Tree = proto.clone().into();
+    assert_eq!(back, tree);
+
+    let checked = CheckedTree::try_from(proto.clone()).unwrap();
+    assert_eq!(checked.grid, vec![vec![2, 4], vec![6]]);
+    assert_eq!(checked.by_name, None);
+
+    let mut big = proto;
+    big.grid[1][0] = 300;
+    let err = CheckedTree::try_from(big).unwrap_err();
+    assert_eq!(err.path(), "grid[1][0]");
+}
@@ -0,0 +1,68 @@
+use std::marker::PhantomData;
+
+use prost_dto::{FromProst, IntoProst, TryFromProst};
+
+mod sub {
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct UserId {
+        pub value: String,
+    }
+    pub struct Point {
+        pub x: i32,
+        pub y: i32,
+    }
+    pub struct User {
+        pub id: Option<UserId>,
+        pub email: String,
+        pub tags: Vec<String>,
+    }
+}
+
+// A newtype mapped onto a single-field message.
+#[derive(Debug, Clone, PartialEq, IntoProst, FromProst)]
+#[prost(target = "sub::UserId")]
+pub struct UserId(#[prost(name = "value")] String);
+
+// Tuple structs map positional fields onto named proto fields.
+#[derive(Debug, PartialEq, IntoProst, TryFromProst)]
+#[prost(target = "sub::Point")]
+pub struct Point(#[prost(name = "x")] i32, #[prost(name = "y")] i32);
+
+// Converts directly to/from the target of the inner type.
+#[derive(Debug, PartialEq, IntoProst, FromProst)]
+#[prost(target = "String", transparent)]
+pub struct Email(String);
+
+#[derive(Debug, PartialEq, IntoProst, FromProst)]
+#[prost(target = "Vec<String>", transparent)]
+pub struct Tags<T>(Vec<String>, #[prost(skip)] PhantomData<T>);
+
+#[derive(Debug, PartialEq, IntoProst, FromProst)]
+#[prost(target = "sub::User")]
+pub struct User {
+    #[prost(required)]
+    pub id: UserId,
+    pub email: Email,
+    pub tags: Tags<()>,
+}
+
+fn main() {
+    let proto: sub::UserId = UserId("u1".to_owned()).into();
+    assert_eq!(proto.value, "u1");
+
+    let proto: sub::Point = Point(1, 2).into();
+    assert_eq!((proto.x, proto.y), (1, 2));
+    assert_eq!(Point::try_from(proto).unwrap(), Point(1, 2));
+
+    let user = User {
+        id: UserId("u1".to_owned()),
+        email: Email("me@example.com".to_owned()),
+        tags: Tags(vec!["a".to_owned()], PhantomData),
+    };
+    let proto: sub::User = user.into();
+    assert_eq!(proto.email, "me@example.com");
+    assert_eq!(proto.tags, vec!["a".to_owned()]);
+    let user: User = proto.into();
+    assert_eq!(user.id, UserId("u1".to_owned()));
+    assert_eq!(user.email, Email("me@example.com".to_owned()));
+}
@@ -0,0 +1,120 @@
+use prost_dto::{ErrorKind, FromProst, IntoProst, TryFromProst};
+
+mod sub {
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Created {
+        pub name: String,
+    }
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Deleted {}
+    pub mod event {
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum Payload {
+            Created(super::Created),
+            Deleted(super::Deleted),
+        }
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum Trace {
+            TraceId(String),
+        }
+    }
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Event {
+        pub id: String,
+        pub payload: Option<event::Payload>,
+        pub trace: Option<event::Trace>,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, IntoProst, FromProst)]
+#[prost(target = "sub::Created")]
+pub struct Created {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, IntoProst, FromProst)]
+#[prost(target = "sub::Event", oneof = "payload", embedded)]
+pub enum Payload {
+    Created(Created),
+    #[prost(target = "sub::Deleted")]
+    Deleted,
+}
+
+#[derive(Debug, Clone, PartialEq, TryFromProst)]
+#[prost(target = "sub::Event", oneof = "payload", embedded)]
+pub enum CheckedPayload {
+    Created(Created),
+    Deleted,
+}
+
+#[derive(Debug, Clone, PartialEq, IntoProst, FromProst)]
+#[prost(target = "sub::Event", oneof = "trace", embedded)]
+pub enum Trace {
+    TraceId(String),
+}
+
+#[derive(Debug, Clone, PartialEq, IntoProst, FromProst)]
+#[prost(target = "sub::Event")]
+pub struct Event {
+    pub id: String,
+    #[prost(oneof)]
+    pub payload: Payload,
+    #[prost(oneof)]
+    pub trace: Option<Trace>,
+}
+
+#[derive(Debug, Clone, PartialEq, TryFromProst)]
+#[prost(target = "sub::Event")]
+pub struct CheckedEvent {
+    pub id: String,
+    #[prost(oneof = "payload")]
+    pub body: CheckedPayload,
+}
+
+fn main() {
+    let event = Event {
+        id: "1".to_string(),
+        payload: Payload::Created(Created {
+            name: "foo".to_string(),
+        }),
+        trace: None,
+    };
+    let proto: sub::Event = event.clone().into();
+    assert_eq!(
+        proto,
+        sub::Event {
+            id: "1".to_string(),
+            payload: Some(sub::event::Payload::Created(sub::Created {
+                name: "foo".to_string(),
+            })),
+            trace: None,
+        }
+    );
+    assert_eq!(Event::from(proto.clone()), event);
+
+    let checked = CheckedEvent::try_from(proto).unwrap();
+    assert_eq!(
+        checked.body,
+        CheckedPayload::Created(Created {
+            name: "foo".to_string()
+        })
+    );
+
+    let proto = sub::Event {
+        id: "2".to_string(),
+        payload: Some(sub::event::Payload::Deleted(sub::Deleted {})),
+        trace: Some(sub::event::Trace::TraceId("abc".to_string())),
+    };
+    let event = Event::from(proto);
+    assert_eq!(event.payload, Payload::Deleted);
+    assert_eq!(event.trace, Some(Trace::TraceId("abc".to_string())));
+
+    let proto = sub::Event {
+        id: "3".to_string(),
+        payload: None,
+        trace: None,
+    };
+    let err = CheckedEvent::try_from(proto).unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::MissingOneof);
+    assert_eq!(err.path(), "payload");
+}
@@ -0,0 +1,92 @@
+use prost_dto::{FromProst, IntoProst, TryFromProst};
+
+mod sub {
+    #[derive(Debug, Clone, Default, PartialEq)]
+    pub struct Stop {}
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Go {
+        pub speed: u32,
+    }
+    pub mod command {
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum Command {
+            // `google.protobuf.Empty`
+            Pause(()),
+            Stop(super::Stop),
+            Go(super::Go),
+        }
+    }
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Command {
+        pub command: Option<command::Command>,
+    }
+
+    pub mod signal {
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum Signal {
+            Stop(super::Stop),
+        }
+    }
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Signal {
+        pub signal: Option<signal::Signal>,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, IntoProst, FromProst)]
+#[prost(target = "sub::Go")]
+pub struct Go {
+    pub speed: u32,
+}
+
+// Unit variants come first, the enum is still mapped to the oneof.
+#[derive(Debug, Clone, PartialEq, IntoProst, FromProst)]
+#[prost(target = "sub::Command")]
+pub enum Command {
+    Pause,
+    #[prost(target = "sub::Stop")]
+    Stop,
+    Go(Go),
+}
+
+#[derive(Debug, Clone, PartialEq, IntoProst, TryFromProst)]
+#[prost(target = "sub::Command")]
+pub enum CheckedCommand {
+    Pause,
+    Stop,
+    Go(Go),
+}
+
+// A unit-only enum is mapped to a oneof when `oneof` is set.
+#[derive(Debug, Clone, PartialEq, IntoProst, FromProst)]
+#[prost(target = "sub::Signal", oneof = "signal")]
+pub enum Signal {
+    Stop,
+}
+
+fn main() {
+    let proto: sub::Command = Command::Pause.into();
+    assert_eq!(proto.command, Some(sub::command::Command::Pause(())));
+    assert_eq!(Command::from(proto), Command::Pause);
+
+    let proto: sub::Command = Command::Stop.into();
+    assert_eq!(
+        proto.command,
+        Some(sub::command::Command::Stop(sub::Stop {}))
+    );
+    assert_eq!(Command::from(proto.clone()), Command::Stop);
+    assert_eq!(
+        CheckedCommand::try_from(proto).unwrap(),
+        CheckedCommand::Stop
+    );
+
+    let proto: sub::Command = Command::Go(Go { speed: 3 }).into();
+    assert_eq!(
+        proto.command,
+        Some(sub::command::Command::Go(sub::Go { speed: 3 }))
+    );
+
+    let proto: sub::Signal = Signal::Stop.into();
+    assert_eq!(proto.signal, Some(sub::signal::Signal::Stop(sub::Stop {})));
+    assert_eq!(Signal::from(proto), Signal::Stop);
+}
@@ -0,0 +1,96 @@
+use prost_dto::ProstDto;
+
+mod proto {
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Account {
+        pub id: String,
+        pub balance_cents: i64,
+        pub owner: Option<Owner>,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Owner {
+        pub name: String,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Default)]
+    pub enum Tier {
+        #[default]
+        Unknown = 0,
+        Basic = 1,
+        Premium = 2,
+    }
+
+    impl From<Tier> for i32 {
+        fn from(value: Tier) -> Self {
+            value as i32
+        }
+    }
+
+    impl TryFrom<i32> for Tier {
+        type Error = i32;
+
+        fn try_from(value: i32) -> Result<Self, Self::Error> {
+            match value {
+                0 => Ok(Tier::Unknown),
+                1 => Ok(Tier::Basic),
+                2 => Ok(Tier::Premium),
+                v => Err(v),
+            }
+        }
+    }
+}
+
+// Dollars as f64 on our side, cents on the wire.
+mod cents {
+    pub fn into_prost(value: f64) -> i64 {
+        (value * 100.0).round() as i64
+    }
+
+    pub fn from_prost(value: i64) -> f64 {
+        value as f64 / 100.0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, ProstDto)]
+#[prost(target = "proto::Owner")]
+pub struct Owner {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ProstDto)]
+#[prost(target = "proto::Tier")]
+pub enum Tier {
+    Basic,
+    Premium,
+}
+
+#[derive(Debug, Clone, PartialEq, ProstDto)]
+#[prost(target = "proto::Account")]
+pub struct Account {
+    pub id: String,
+    #[prost(name = "balance_cents", with = "cents")]
+    pub balance: f64,
+    pub owner: Option<Owner>,
+    #[prost(skip)]
+    pub cached: bool,
+}
+
+fn main() {
+    let account = Account {
+        id: "acc-1".to_owned(),
+        balance: 12.34,
+        owner: Some(Owner {
+            name: "Ada".to_owned(),
+        }),
+        cached: false,
+    };
+    let proto: proto::Account = account.clone().into();
+    assert_eq!(proto.balance_cents, 1234);
+    assert_eq!(proto.owner.as_ref().unwrap().name, "Ada");
+
+    let back: Account = proto.into();
+    assert_eq!(back, account);
+
+    assert_eq!(Tier::from(proto::Tier::Basic), Tier::Basic);
+}
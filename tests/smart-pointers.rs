@@ -0,0 +1,105 @@
+use std::borrow::Cow;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use prost_dto::{FromProst, IntoProst, TryFromProst};
+
+mod proto {
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Config {
+        pub name: String,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Node {
+        pub value: u32,
+        pub label: String,
+        pub parent: Option<Box<Node>>,
+        pub children: Vec<Node>,
+        pub config: Option<Config>,
+        pub origin: Option<Box<Config>>,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, IntoProst, FromProst)]
+#[prost(target = "proto::Config")]
+pub struct Config {
+    pub name: String,
+}
+
+// A recursive message, prost boxes `parent`.
+#[derive(Debug, Clone, PartialEq, IntoProst, FromProst)]
+#[prost(target = "proto::Node")]
+pub struct Node {
+    pub value: u32,
+    pub label: Cow<'static, str>,
+    #[prost(boxed)]
+    pub parent: Option<Arc<Node>>,
+    pub children: Vec<Arc<Node>>,
+    #[prost(required)]
+    pub config: Arc<Config>,
+    #[prost(boxed)]
+    pub origin: Option<Config>,
+}
+
+#[derive(Debug, Clone, PartialEq, TryFromProst)]
+#[prost(target = "proto::Node")]
+pub struct CheckedNode {
+    #[from_prost(try_map = "u8::try_from")]
+    pub value: Box<u8>,
+    pub label: String,
+    #[prost(boxed)]
+    pub parent: Option<Box<CheckedNode>>,
+    pub children: Vec<Rc<CheckedNode>>,
+    #[prost(required)]
+    pub config: Box<Config>,
+    #[prost(boxed)]
+    pub origin: Option<Config>,
+}
+
+fn leaf(value: u32) -> Node {
+    Node {
+        value,
+        label: Cow::Borrowed("leaf"),
+        parent: None,
+        children: vec![],
+        config: Arc::new(Config {
+            name: "default".to_owned(),
+        }),
+        origin: None,
+    }
+}
+
+fn main() {
+    let node = Node {
+        parent: Some(Arc::new(leaf(1))),
+        children: vec![Arc::new(leaf(2)), Arc::new(leaf(3))],
+        origin: Some(Config {
+            name: "origin".to_owned(),
+        }),
+        ..leaf(4)
+    };
+    let proto: proto::Node = node.clone().into();
+    assert_eq!(proto.parent.as_ref().unwrap().value, 1);
+    assert_eq!(proto.children[1].value, 3);
+    assert_eq!(proto.label, "leaf");
+    assert_eq!(proto.config.as_ref().unwrap().name, "default");
+    assert_eq!(proto.origin.as_ref().unwrap().name, "origin");
+
+    let back: Node = proto.clone().into();
+    assert_eq!(back, node);
+
+    let checked = CheckedNode::try_from(proto.clone()).unwrap();
+    assert_eq!(*checked.parent.unwrap().value, 1);
+    assert_eq!(*checked.children[0].value, 2);
+
+    let mut missing = proto.clone();
+    missing.parent.as_mut().unwrap().config = None;
+    let err = CheckedNode::try_from(missing).unwrap_err();
+    assert_eq!(err.path(), "parent.config");
+
+    let mut big = proto;
+    big.children[1].value = 300;
+    let err = CheckedNode::try_from(big).unwrap_err();
+    assert_eq!(err.path(), "children[1].value");
+}
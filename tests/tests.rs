@@ -6,4 +6,34 @@ fn dto_tests() {
     t.pass("./tests/simple-into-prost-struct.rs");
     t.pass("./tests/from-prost-enum-unit.rs");
     t.pass("./tests/simple-from-prost-struct.rs");
+    t.pass("./tests/try-from-prost.rs");
+    t.pass("./tests/try-from-prost-error-path.rs");
+    t.pass("./tests/generic-dto.rs");
+    t.pass("./tests/newtype-dto.rs");
+    t.pass("./tests/enum-struct-variant.rs");
+    t.pass("./tests/oneof-unit-variant.rs");
+    t.pass("./tests/oneof-field.rs");
+    t.pass("./tests/enum-unknown.rs");
+    t.pass("./tests/enum-other.rs");
+    t.pass("./tests/enumeration-field.rs");
+    t.pass("./tests/wrapper-types.rs");
+    t.pass("./tests/field-paths.rs");
+    t.pass("./tests/by-ref.rs");
+    t.pass("./tests/prost-dto.rs");
+    t.pass("./tests/map-expr.rs");
+    t.pass("./tests/nested-containers.rs");
+    t.pass("./tests/smart-pointers.rs");
+    t.pass("./tests/collections.rs");
+    t.pass("./tests/map-keys.rs");
+    t.pass("./tests/arrays.rs");
+    #[cfg(feature = "prost-types")]
+    t.pass("./tests/any-types.rs");
+    #[cfg(feature = "prost-types")]
+    t.pass("./tests/field-mask-patch.rs");
+    #[cfg(all(feature = "chrono", feature = "time"))]
+    t.pass("./tests/well-known-types.rs");
+    #[cfg(feature = "serde_json")]
+    t.pass("./tests/json-types.rs");
+    #[cfg(all(feature = "bytes", feature = "uuid"))]
+    t.pass("./tests/bytes-types.rs");
 }
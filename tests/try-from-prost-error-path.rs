@@ -0,0 +1,133 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::num::ParseIntError;
+
+use prost_dto::{ErrorKind, PathSegment, TryFromProst};
+
+mod sub {
+    use std::collections::BTreeMap;
+
+    pub struct Timepoint {
+        pub epoch: String,
+    }
+    pub struct RunAt {
+        pub run_at: Vec<Timepoint>,
+    }
+    pub struct Cron {
+        pub pattern: String,
+    }
+    pub mod schedule {
+        pub enum Schedule {
+            Cron(super::Cron),
+            RunAt(super::RunAt),
+        }
+    }
+    pub struct Schedule {
+        pub schedule: Option<schedule::Schedule>,
+    }
+    pub struct Job {
+        pub schedule: Option<Schedule>,
+        pub deadlines: BTreeMap<String, Timepoint>,
+    }
+}
+
+fn parse_epoch(epoch: &str) -> Result<u64, ParseIntError> {
+    epoch.parse()
+}
+
+#[derive(Debug, TryFromProst)]
+#[prost(target = "sub::Timepoint")]
+pub struct Timepoint {
+    #[from_prost(try_map = "parse_epoch", map_by_ref)]
+    pub epoch: u64,
+}
+
+#[derive(Debug, TryFromProst)]
+#[prost(target = "sub::Cron")]
+pub struct Cron {
+    pub pattern: String,
+}
+
+#[derive(Debug, TryFromProst)]
+#[prost(target = "sub::RunAt")]
+pub struct RunAt {
+    #[prost(name = "run_at")]
+    pub timepoints: Vec<Timepoint>,
+}
+
+#[derive(Debug, TryFromProst)]
+#[prost(target = "sub::Schedule", oneof = "schedule")]
+pub enum Schedule {
+    #[prost(name = "Cron")]
+    Recurring(Cron),
+    RunAt(RunAt),
+}
+
+#[derive(Debug, TryFromProst)]
+#[prost(target = "sub::Job")]
+pub struct Job {
+    #[prost(required)]
+    pub schedule: Schedule,
+    pub deadlines: BTreeMap<String, Timepoint>,
+}
+
+fn timepoint(epoch: &str) -> sub::Timepoint {
+    sub::Timepoint {
+        epoch: epoch.to_owned(),
+    }
+}
+
+fn main() {
+    let job = |timepoints: Vec<sub::Timepoint>, deadline: &str| sub::Job {
+        schedule: Some(sub::Schedule {
+            schedule: Some(sub::schedule::Schedule::RunAt(sub::RunAt {
+                run_at: timepoints,
+            })),
+        }),
+        deadlines: BTreeMap::from([("soft".to_owned(), timepoint(deadline))]),
+    };
+
+    let converted = Job::try_from(job(vec![timepoint("1"), timepoint("2")], "3")).unwrap();
+    assert_eq!(converted.deadlines["soft"].epoch, 3);
+
+    // nested messages, oneof variants and repeated indices
+    let proto = job(
+        vec![
+            timepoint("1"),
+            timepoint("2"),
+            timepoint("3"),
+            timepoint("x"),
+        ],
+        "3",
+    );
+    let err = Job::try_from(proto).unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::Mapper);
+    assert_eq!(err.path(), "schedule.run_at.run_at[3].epoch");
+    assert_eq!(err.field(), Some("epoch"));
+    assert_eq!(
+        err.segments().cloned().collect::<Vec<_>>(),
+        vec![
+            PathSegment::Field("schedule"),
+            PathSegment::Field("run_at"),
+            PathSegment::Field("run_at"),
+            PathSegment::Index(3),
+            PathSegment::Field("epoch"),
+        ]
+    );
+    assert!(err.source().unwrap().is::<ParseIntError>());
+    assert_eq!(
+        err.to_string(),
+        "schedule.run_at.run_at[3].epoch: mapper failed: invalid digit found in string"
+    );
+
+    // map values
+    let err = Job::try_from(job(vec![], "-")).unwrap_err();
+    assert_eq!(err.path(), r#"deadlines["soft"].epoch"#);
+
+    // missing oneof in a nested message
+    let mut proto = job(vec![], "1");
+    proto.schedule = Some(sub::Schedule { schedule: None });
+    let err = Job::try_from(proto).unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::MissingOneof);
+    assert_eq!(err.path(), "schedule.schedule");
+}
@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+
+use prost_dto::{ErrorKind, TryFromProst};
+
+mod sub {
+    use std::collections::HashMap;
+
+    #[repr(i32)]
+    #[derive(Debug)]
+    pub enum HttpMethod {
+        Unknown = 0,
+        Get = 1,
+        Post = 2,
+    }
+
+    impl TryFrom<i32> for HttpMethod {
+        type Error = i32;
+
+        fn try_from(input: i32) -> Result<Self, i32> {
+            match input {
+                0 => Ok(Self::Unknown),
+                1 => Ok(Self::Get),
+                2 => Ok(Self::Post),
+                _ => Err(input),
+            }
+        }
+    }
+
+    pub struct Target {
+        pub url: String,
+        pub method: i32,
+    }
+
+    pub struct Cron {
+        pub pattern: String,
+    }
+
+    pub mod schedule {
+        pub enum Schedule {
+            Cron(super::Cron),
+        }
+    }
+
+    pub struct Schedule {
+        pub schedule: Option<schedule::Schedule>,
+    }
+
+    pub struct Job {
+        pub target: Option<Target>,
+        pub schedule: Option<Schedule>,
+        pub methods: Vec<i32>,
+        pub labels: HashMap<String, i32>,
+    }
+}
+
+#[derive(Debug, PartialEq, TryFromProst)]
+#[prost(target = "sub::HttpMethod")]
+pub enum HttpMethod {
+    Get,
+    Post,
+}
+
+#[derive(Debug, PartialEq, TryFromProst)]
+#[prost(target = "sub::Target")]
+pub struct Target {
+    pub url: String,
+    pub method: HttpMethod,
+}
+
+#[derive(Debug, PartialEq, TryFromProst)]
+#[prost(target = "sub::Cron")]
+pub struct Cron {
+    pub pattern: String,
+}
+
+#[derive(Debug, PartialEq, TryFromProst)]
+#[prost(target = "sub::Schedule")]
+pub enum Schedule {
+    Cron(Cron),
+}
+
+#[derive(Debug, PartialEq, TryFromProst)]
+#[prost(target = "sub::Job")]
+pub struct Job {
+    #[prost(required)]
+    pub target: Target,
+    pub schedule: Option<Schedule>,
+    pub methods: Vec<HttpMethod>,
+    pub labels: HashMap<String, HttpMethod>,
+}
+
+fn job() -> sub::Job {
+    sub::Job {
+        target: Some(sub::Target {
+            url: "http://localhost".to_owned(),
+            method: 1,
+        }),
+        schedule: Some(sub::Schedule {
+            schedule: Some(sub::schedule::Schedule::Cron(sub::Cron {
+                pattern: "* * * * *".to_owned(),
+            })),
+        }),
+        methods: vec![1, 2],
+        labels: HashMap::from([("a".to_owned(), 2)]),
+    }
+}
+
+fn main() {
+    let converted = Job::try_from(job()).unwrap();
+    assert_eq!(converted.target.method, HttpMethod::Get);
+    assert_eq!(converted.methods, vec![HttpMethod::Get, HttpMethod::Post]);
+    assert_eq!(
+        converted.schedule,
+        Some(Schedule::Cron(Cron {
+            pattern: "* * * * *".to_owned()
+        }))
+    );
+
+    // missing required field
+    let mut proto = job();
+    proto.target = None;
+    let err = Job::try_from(proto).unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::MissingRequired);
+    assert_eq!(err.field(), Some("target"));
+    assert_eq!(err.to_string(), "target: missing required field");
+
+    // missing oneof
+    let mut proto = job();
+    proto.schedule = Some(sub::Schedule { schedule: None });
+    let err = Job::try_from(proto).unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::MissingOneof);
+
+    // unknown enum values, including the `Unknown` sentinel.
+    for value in [0, 42] {
+        let mut proto = job();
+        proto.methods = vec![1, value];
+        let err = Job::try_from(proto).unwrap_err();
+        assert_eq!(
+            err.kind(),
+            &ErrorKind::UnknownEnumValue {
+                enum_name: "sub::HttpMethod",
+                value
+            }
+        );
+    }
+}
@@ -0,0 +1,145 @@
+use std::time::{Duration, UNIX_EPOCH};
+
+use prost_dto::{ErrorKind, IntoProst, TryFromProst};
+
+mod sub {
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Job {
+        pub created_at: Option<prost_types::Timestamp>,
+        pub updated_at: Option<prost_types::Timestamp>,
+        pub deleted_at: Option<prost_types::Timestamp>,
+        pub timeout: Option<prost_types::Duration>,
+        pub retries: Vec<prost_types::Duration>,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, IntoProst)]
+#[prost(target = "sub::Job")]
+pub struct Job {
+    #[prost(required)]
+    pub created_at: std::time::SystemTime,
+    pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub deleted_at: Option<time::OffsetDateTime>,
+    #[prost(well_known, required)]
+    pub timeout: Duration,
+    pub retries: Vec<std::time::Duration>,
+}
+
+#[derive(Debug, Clone, PartialEq, TryFromProst)]
+#[prost(target = "sub::Job")]
+pub struct CheckedJob {
+    #[prost(required)]
+    pub created_at: std::time::SystemTime,
+    pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub deleted_at: Option<time::OffsetDateTime>,
+    #[prost(well_known, required)]
+    pub timeout: Duration,
+    pub retries: Vec<std::time::Duration>,
+}
+
+fn main() {
+    let job = Job {
+        created_at: UNIX_EPOCH + Duration::from_millis(1_500),
+        updated_at: chrono::DateTime::from_timestamp(10, 5),
+        deleted_at: Some(time::OffsetDateTime::from_unix_timestamp(20).unwrap()),
+        timeout: Duration::from_secs(30),
+        retries: vec![Duration::from_millis(100)],
+    };
+    let proto: sub::Job = job.clone().into();
+    assert_eq!(
+        proto.created_at,
+        Some(prost_types::Timestamp {
+            seconds: 1,
+            nanos: 500_000_000
+        })
+    );
+    assert_eq!(
+        proto.updated_at,
+        Some(prost_types::Timestamp {
+            seconds: 10,
+            nanos: 5
+        })
+    );
+    assert_eq!(
+        proto.timeout,
+        Some(prost_types::Duration {
+            seconds: 30,
+            nanos: 0
+        })
+    );
+    let checked = CheckedJob::try_from(proto.clone()).unwrap();
+    assert_eq!(checked.created_at, job.created_at);
+    assert_eq!(checked.updated_at, job.updated_at);
+    assert_eq!(checked.deleted_at, job.deleted_at);
+    assert_eq!(checked.timeout, job.timeout);
+    assert_eq!(checked.retries, job.retries);
+
+    // Leap seconds are normalized.
+    let leap = chrono::NaiveDate::from_ymd_opt(2016, 12, 31)
+        .unwrap()
+        .and_hms_nano_opt(23, 59, 59, 1_500_000_000)
+        .unwrap()
+        .and_utc();
+    let leap_proto: sub::Job = Job {
+        updated_at: Some(leap),
+        ..job.clone()
+    }
+    .into();
+    assert_eq!(
+        leap_proto.updated_at,
+        Some(prost_types::Timestamp {
+            seconds: 1_483_228_800,
+            nanos: 500_000_000
+        })
+    );
+
+    // Negative durations can't be represented.
+    let mut negative = proto;
+    negative.retries = vec![prost_types::Duration {
+        seconds: -1,
+        nanos: 0,
+    }];
+    let err = CheckedJob::try_from(negative).unwrap_err();
+    assert_eq!(
+        err.kind(),
+        &ErrorKind::OutOfRange {
+            type_name: "google.protobuf.Duration"
+        }
+    );
+    assert_eq!(err.path(), "retries[0]");
+
+    // Values beyond the range of the well-known types are clamped to it.
+    let timestamp = |seconds, nanos| Some(prost_types::Timestamp { seconds, nanos });
+    let duration = |seconds, nanos| Some(prost_types::Duration { seconds, nanos });
+    let last: sub::Job = Job {
+        created_at: UNIX_EPOCH + Duration::new(253_402_300_799, 999_999_999),
+        updated_at: chrono::DateTime::from_timestamp(253_402_300_799, 0),
+        deleted_at: Some(time::OffsetDateTime::from_unix_timestamp(-62_135_596_800).unwrap()),
+        timeout: Duration::new(315_576_000_000, 999_999_999),
+        ..job.clone()
+    }
+    .into();
+    assert_eq!(last.created_at, timestamp(253_402_300_799, 999_999_999));
+    assert_eq!(last.updated_at, timestamp(253_402_300_799, 0));
+    assert_eq!(last.deleted_at, timestamp(-62_135_596_800, 0));
+    assert_eq!(last.timeout, duration(315_576_000_000, 999_999_999));
+    let beyond: sub::Job = Job {
+        created_at: UNIX_EPOCH + Duration::from_secs(253_402_300_800),
+        updated_at: chrono::DateTime::from_timestamp(-62_135_596_801, 0),
+        deleted_at: Some(time::OffsetDateTime::from_unix_timestamp(-62_135_596_801).unwrap()),
+        timeout: Duration::from_secs(315_576_000_001),
+        retries: vec![Duration::MAX],
+    }
+    .into();
+    assert_eq!(beyond.created_at, timestamp(253_402_300_799, 999_999_999));
+    assert_eq!(beyond.updated_at, timestamp(-62_135_596_800, 0));
+    assert_eq!(beyond.deleted_at, timestamp(-62_135_596_800, 0));
+    assert_eq!(beyond.timeout, duration(315_576_000_000, 999_999_999));
+    assert_eq!(
+        beyond.retries,
+        vec![prost_types::Duration {
+            seconds: 315_576_000_000,
+            nanos: 999_999_999
+        }]
+    );
+}
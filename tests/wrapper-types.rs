@@ -0,0 +1,75 @@
+use prost_dto::{ErrorKind, FromProst, IntoProst, TryFromProst};
+
+mod sub {
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct StringValue {
+        pub value: String,
+    }
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Int64Value {
+        pub value: i64,
+    }
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct BoolValue {
+        pub value: bool,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Profile {
+        pub nickname: Option<StringValue>,
+        pub age: Option<Int64Value>,
+        pub verified: Option<BoolValue>,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, IntoProst, FromProst)]
+#[prost(target = "sub::Profile")]
+pub struct Profile {
+    #[prost(wrapper = "sub::StringValue")]
+    pub nickname: Option<String>,
+    #[prost(wrapper = "sub::Int64Value")]
+    pub age: Option<i64>,
+    #[prost(wrapper = "sub::BoolValue")]
+    pub verified: Option<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, TryFromProst)]
+#[prost(target = "sub::Profile")]
+pub struct CheckedProfile {
+    #[prost(wrapper)]
+    pub nickname: Option<String>,
+    #[prost(wrapper)]
+    #[from_prost(try_map = "u8::try_from")]
+    pub age: Option<u8>,
+    #[prost(skip)]
+    pub verified: Option<bool>,
+}
+
+fn main() {
+    let profile = Profile {
+        nickname: Some("foo".to_owned()),
+        age: Some(42),
+        verified: None,
+    };
+    let proto: sub::Profile = profile.clone().into();
+    assert_eq!(
+        proto,
+        sub::Profile {
+            nickname: Some(sub::StringValue {
+                value: "foo".to_owned()
+            }),
+            age: Some(sub::Int64Value { value: 42 }),
+            verified: None,
+        }
+    );
+    assert_eq!(Profile::from(proto.clone()), profile);
+
+    let checked = CheckedProfile::try_from(proto.clone()).unwrap();
+    assert_eq!(checked.age, Some(42));
+
+    let mut proto = proto;
+    proto.age = Some(sub::Int64Value { value: 1000 });
+    let err = CheckedProfile::try_from(proto).unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::Mapper);
+    assert_eq!(err.path(), "age");
+}